@@ -0,0 +1,71 @@
+//! Cost of creating errors with the different macros
+//!
+//! Run with: `cargo bench --bench error_creation`
+//!
+//! Medians on a shared x86_64 Linux VM, `--measurement-time 8`, where runs vary by
+//! 10 to 20%. Before is a `String` message, copied from the literal, after is the
+//! borrowed `Cow` message of `__message`, in back to back runs:
+//!
+//! | Bench | Before | After |
+//! |---|---|---|
+//! | debug_error literal | 121 ns | 107 ns |
+//! | debug_error formatted | 151 ns | 149 ns |
+//! | debug_error_with_log | 654 ns | 536 ns |
+//! | debug_error with source chain | 376 ns | 346 ns |
+//!
+//! The literal case saves the allocation of the message. Because the machine is
+//! noisy, `debug_error literal, owned` repeats the old path in the same run: it stays
+//! 13 to 15% slower than `debug_error literal` (110 vs 95 ns, 126 vs 111 ns). The
+//! other differences are within the noise, formatted messages still allocate.
+
+use std::hint::black_box;
+use std::panic::Location;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use debug_error::{debug_error, debug_error_with_log, DebugError};
+
+/// Logger that drops all records, so the bench measures the macro and not the output
+struct NullLogger;
+
+impl log::Log for NullLogger 
+{
+    fn enabled(&self, _: &log::Metadata) -> bool 
+    {
+        true
+    }
+
+    fn log(&self, _: &log::Record) 
+    {
+    }
+
+    fn flush(&self) 
+    {
+    }
+}
+
+fn error_creation(c: &mut Criterion) 
+{
+    log::set_logger(&NullLogger).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+
+    c.bench_function("debug_error literal", |b| b.iter(|| debug_error!("Connection refused")));
+    // What a literal cost before the fast path, measured in the same run for comparison
+    c.bench_function("debug_error literal, owned", |b| {
+        b.iter(|| DebugError::new(black_box("Connection refused").to_string(), Location::caller()))
+    });
+    c.bench_function("debug_error formatted", |b| {
+        let port = black_box(5432);
+        b.iter(|| debug_error!("Connection to port {} refused", port))
+    });
+    c.bench_function("debug_error_with_log", |b| b.iter(|| debug_error_with_log!("Connection refused")));
+    c.bench_function("debug_error with source chain", |b| {
+        b.iter(|| {
+            let io_err = std::io::Error::other("connection reset");
+            let inner = debug_error!("Query failed").with_source(io_err);
+            debug_error!("Loading orders failed").with_source(inner)
+        })
+    });
+}
+
+criterion_group!(benches, error_creation);
+criterion_main!(benches);
@@ -0,0 +1,30 @@
+//! Build script, embeds the git commit hash when the `build-info` feature is enabled
+
+use std::process::Command;
+
+fn main() 
+{
+    if std::env::var_os("CARGO_FEATURE_BUILD_INFO").is_none() {
+        return;
+    }
+    println!("cargo:rerun-if-env-changed=GIT_HASH");
+    if std::path::Path::new(".git/HEAD").exists() {
+        println!("cargo:rerun-if-changed=.git/HEAD");
+    }
+
+    // An explicit `GIT_HASH` wins, e.g. for CI builds from a source tarball
+    let hash = std::env::var("GIT_HASH").ok().filter(|hash| !hash.is_empty()).or_else(git_hash);
+    if let Some(hash) = hash {
+        println!("cargo:rustc-env=DEBUG_ERROR_GIT_HASH={}", hash);
+    }
+}
+
+fn git_hash() -> Option<String> 
+{
+    let output = Command::new("git").args(["rev-parse", "--short", "HEAD"]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let hash = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!hash.is_empty()).then_some(hash)
+}
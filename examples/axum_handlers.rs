@@ -0,0 +1,33 @@
+//! Example showing `http_bail!` in axum handlers
+//!
+//! Run with: `cargo run --example axum_handlers --features axum`
+
+use axum::extract::Path;
+use axum::response::IntoResponse;
+use debug_error::{http_bail, DebugError};
+use log::info;
+
+async fn get_user(Path(id): Path<u32>) -> Result<String, DebugError> {
+    if id == 0 {
+        http_bail!(400, "User id must not be 0");
+    }
+    if id > 100 {
+        // The location of this line is logged on the server, the client only gets a 404
+        http_bail!(404, "User {} not found in table users", id);
+    }
+    Ok(format!("User {}", id))
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    env_logger::init();
+
+    info!("Starting axum handlers example");
+
+    // Handlers are plain async functions, so we can call them without a server.
+    // In a real service: Router::new().route("/users/{id}", get(get_user))
+    for id in [7, 0, 101] {
+        let response = get_user(Path(id)).await.into_response();
+        println!("GET /users/{} -> {}", id, response.status());
+    }
+}
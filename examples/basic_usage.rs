@@ -2,6 +2,7 @@
 //!
 //! Run with: `cargo run --example basic_usage`
 //! Set log level: `RUST_LOG=info cargo run --example basic_usage`
+//! Without env_logger: `cargo run --example basic_usage --features simple-logger`
 //! 
 //! For transforming errors user .map_err(|e| debug_error!("your_message: {}", e))?;
 //! See more in examples/real_world_scenario.rs
@@ -11,6 +12,9 @@ use log::{info, error};
 
 fn main() {
     // Initialize logger
+    #[cfg(feature = "simple-logger")]
+    debug_error::init_simple_logger(log::Level::Info);
+    #[cfg(not(feature = "simple-logger"))]
     env_logger::init();
     
     info!("Starting basic usage example");
@@ -27,6 +31,9 @@ fn main() {
         Err(e) => error!("debug_error_with_log failed: {}", e),
     }
     
+    // Test log_self (re-logging an existing error)
+    test_log_self();
+    
     info!("Example completed");
 }
 
@@ -47,4 +54,13 @@ fn test_debug_error_with_log() -> Result<(), DebugError> {
     // This creates an error AND logs it automatically
     debug_error_with_log!("This error is automatically logged");
     Ok(())
+}
+
+fn test_log_self() {
+    info!("Testing log_self...");
+    
+    let err = debug_error!("This error is logged later");
+    // Logs both where the error was created and where it is logged:
+    // Error: This error is logged later at examples\basic_usage.rs:58:15 (logged at examples\basic_usage.rs:61:9)
+    err.log_self();
 }
\ No newline at end of file
@@ -0,0 +1,54 @@
+//! Example showing per-module error code prefixes
+//!
+//! Run with: `cargo run --example error_codes`
+
+use debug_error::{DebugError, summarize};
+use log::{error, info};
+
+mod db {
+    use debug_error::{DebugError, debug_error_coded, define_error_prefix};
+
+    // Every code created in this module starts with DB-
+    define_error_prefix!("DB");
+
+    pub fn connect(host: &str) -> Result<(), DebugError> {
+        Err(debug_error_coded!(1, "Connection to {} refused", host))
+    }
+
+    pub fn query(table: &str) -> Result<(), DebugError> {
+        Err(debug_error_coded!(7, "Table {} does not exist", table))
+    }
+}
+
+mod http {
+    use debug_error::{DebugError, debug_error_coded, define_error_prefix};
+
+    // Every code created in this module starts with HTTP-
+    define_error_prefix!("HTTP");
+
+    pub fn fetch(url: &str) -> Result<(), DebugError> {
+        Err(debug_error_coded!(42, "Request to {} timed out", url))
+    }
+}
+
+fn main() {
+    env_logger::init();
+    
+    info!("Starting error codes example");
+    
+    let errors: Vec<DebugError> = [
+        db::connect("localhost:5432"),
+        db::query("users"),
+        http::fetch("https://example.com"),
+    ]
+    .into_iter()
+    .filter_map(Result::err)
+    .collect();
+    
+    for err in &errors {
+        // Prints e.g. [DB-001] Connection to localhost:5432 refused at examples/error_codes.rs:15:13
+        error!("[{}] {}", err.code().unwrap_or("-"), err);
+    }
+    
+    info!("Summary:\n{}", summarize(&errors));
+}
@@ -3,7 +3,7 @@
 //! Run with: `cargo run --example real_world_scenario`
 //! Debug mode: `RUST_LOG=debug cargo run --example real_world_scenario`
 
-use debug_error::{DebugError, debug_error, debug_error_with_log};
+use debug_error::{DebugError, debug_error, debug_error_with_log, try_block};
 use log::{debug, error, info, warn};
 
 #[derive(Debug)]
@@ -61,6 +61,14 @@ fn main() -> Result<(), DebugError> {
     let _ = process_user_data(&db, 123)
         .map_err(|e| { warn!("Example 5 failed: {}", e); e });
     
+    // Example 6: Using ? inside a block with context
+    let _ = try_block!("while loading user profiles", {
+        let alice = db.get_user(123)?;
+        let nobody = db.get_user(0)?;
+        Ok((alice, nobody))
+    })
+    .map_err(|e| { warn!("Example 6 failed: {}", e); e });
+    
     info!("Real-world example completed (some examples may have failed as expected)");
 
     info!("The next line will let the program fail due to use of ? in main while an error occurs\n
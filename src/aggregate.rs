@@ -0,0 +1,187 @@
+//! Collections of errors, e.g. all validation failures of a form or a batch
+
+use crate::DebugError;
+
+/// Errors collected while processing many items, in the order they were pushed
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error, DebugErrors};
+///
+/// let mut errors = DebugErrors::new();
+/// errors.push(debug_error!("Row 3: missing email"));
+/// errors.push(debug_error!("Row 8: invalid date"));
+/// errors.push(debug_error!("Row 9: duplicate id"));
+/// assert_eq!(errors.len(), 3);
+///
+/// let messages: Vec<String> = errors.into_iter().map(|err| err.message.into_owned()).collect();
+/// assert_eq!(messages, ["Row 3: missing email", "Row 8: invalid date", "Row 9: duplicate id"]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DebugErrors 
+{
+    errors: Vec<DebugError>,
+}
+
+impl DebugErrors 
+{
+    pub fn new() -> Self 
+    {
+        Self::default()
+    }
+
+    /// Adds an error at the end
+    pub fn push(&mut self, err: DebugError) 
+    {
+        self.errors.push(err);
+    }
+
+    /// Returns the number of errors
+    pub fn len(&self) -> usize 
+    {
+        self.errors.len()
+    }
+
+    /// Returns true if there are no errors
+    pub fn is_empty(&self) -> bool 
+    {
+        self.errors.is_empty()
+    }
+
+    /// Iterates over the errors in the order they were pushed
+    pub fn iter(&self) -> std::slice::Iter<'_, DebugError> 
+    {
+        self.errors.iter()
+    }
+
+    /// Logs each error at its level and removes it, leaving the collection empty
+    ///
+    /// Errors that were logged already (see `DebugError::is_logged`) are removed
+    /// without being logged again.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{debug_error, DebugErrors};
+    /// # debug_error::__capture_logs();
+    ///
+    /// let mut errors: DebugErrors = vec![
+    ///     debug_error!("Row 3: missing email"),
+    ///     debug_error!("Row 8: invalid date").with_level(log::Level::Warn),
+    ///     debug_error!("Row 9: duplicate id"),
+    /// ]
+    /// .into_iter()
+    /// .collect();
+    ///
+    /// errors.drain_logged();
+    /// assert!(errors.is_empty());
+    ///
+    /// let records = debug_error::__captured_logs();
+    /// assert_eq!(records.len(), 3);
+    /// assert!(records[0].message.starts_with("Error: Row 3: missing email at "));
+    /// assert_eq!(records[1].level, log::Level::Warn);
+    /// assert!(records[2].message.starts_with("Error: Row 9: duplicate id at "));
+    /// ```
+    pub fn drain_logged(&mut self) 
+    {
+        for err in self.errors.drain(..) {
+            err.log_unless_logged();
+        }
+    }
+}
+
+impl From<Vec<DebugError>> for DebugErrors 
+{
+    fn from(errors: Vec<DebugError>) -> Self 
+    {
+        Self { errors }
+    }
+}
+
+impl FromIterator<DebugError> for DebugErrors 
+{
+    fn from_iter<I: IntoIterator<Item = DebugError>>(iter: I) -> Self 
+    {
+        Self { errors: iter.into_iter().collect() }
+    }
+}
+
+impl Extend<DebugError> for DebugErrors 
+{
+    fn extend<I: IntoIterator<Item = DebugError>>(&mut self, iter: I) 
+    {
+        self.errors.extend(iter);
+    }
+}
+
+impl IntoIterator for DebugErrors 
+{
+    type Item = DebugError;
+    type IntoIter = std::vec::IntoIter<DebugError>;
+
+    fn into_iter(self) -> Self::IntoIter 
+    {
+        self.errors.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a DebugErrors 
+{
+    type Item = &'a DebugError;
+    type IntoIter = std::slice::Iter<'a, DebugError>;
+
+    fn into_iter(self) -> Self::IntoIter 
+    {
+        self.errors.iter()
+    }
+}
+
+/// Unwraps all results, or returns every error if any of them failed
+///
+/// Each error is logged at its level as it's encountered, unless it was logged
+/// already, so the returned `DebugErrors` only needs to be handled, not logged again.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{combine_results, debug_error, DebugError};
+/// # debug_error::__capture_logs();
+///
+/// let results: Vec<Result<u32, DebugError>> = vec![
+///     Ok(1),
+///     Err(debug_error!("Shard 2 unreachable")),
+///     Ok(3),
+///     Err(debug_error!("Shard 4 timed out")),
+/// ];
+///
+/// let errors = combine_results(results).unwrap_err();
+/// let messages: Vec<&str> = errors.iter().map(|err| &*err.message).collect();
+/// assert_eq!(messages, ["Shard 2 unreachable", "Shard 4 timed out"]);
+///
+/// let records = debug_error::__captured_logs();
+/// assert_eq!(records.len(), 2);
+/// assert!(records[0].message.contains("Shard 2 unreachable"));
+/// assert!(records[1].message.contains("Shard 4 timed out"));
+///
+/// assert_eq!(combine_results(vec![Ok(1), Ok(2)]).unwrap(), [1, 2]);
+/// ```
+pub fn combine_results<T>(results: Vec<Result<T, DebugError>>) -> Result<Vec<T>, DebugErrors> 
+{
+    let mut values = Vec::with_capacity(results.len());
+    let mut errors = DebugErrors::new();
+    for result in results {
+        match result {
+            Ok(value) => values.push(value),
+            Err(err) => {
+                err.log_unless_logged();
+                errors.push(err);
+            }
+        }
+    }
+    if errors.is_empty() {
+        Ok(values)
+    } else {
+        Err(errors)
+    }
+}
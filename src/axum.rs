@@ -0,0 +1,43 @@
+//! HTTP responses for handlers built on `axum`
+
+use ::axum::http::StatusCode;
+use ::axum::response::{IntoResponse, Response};
+
+use crate::DebugError;
+
+/// Turns an error into a plain text response
+///
+/// The status is the one set with `http_bail!` or `DebugError::with_http_status`,
+/// otherwise the one of the kind, otherwise 500. The body is
+/// `DebugError::public_message`, so internal details never reach the client. The
+/// complete error is logged at its level before converting, unless it was logged already.
+///
+/// # Examples
+///
+/// ```rust
+/// use axum::http::StatusCode;
+/// use axum::response::IntoResponse;
+/// use debug_error::{debug_error, Kind};
+///
+/// let response = debug_error!("No row for id 7").with_http_status(404).into_response();
+/// assert_eq!(response.status(), StatusCode::NOT_FOUND);
+///
+/// let response = debug_error!("Token expired").with_kind(Kind::PermissionDenied).into_response();
+/// assert_eq!(response.status(), StatusCode::FORBIDDEN);
+///
+/// let response = debug_error!("Pool exhausted").into_response();
+/// assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+/// ```
+impl IntoResponse for DebugError 
+{
+    fn into_response(self) -> Response 
+    {
+        self.log_unless_logged();
+        let status = self
+            .http_status()
+            .or_else(|| self.kind().map(|kind| kind.http_status()))
+            .and_then(|status| StatusCode::from_u16(status).ok())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        (status, self.public_message()).into_response()
+    }
+}
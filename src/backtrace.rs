@@ -0,0 +1,66 @@
+//! Backtraces of errors, enabled with the `backtrace` feature
+//!
+//! Errors capture a `std::backtrace::Backtrace` when they are created. Like for
+//! panics, capturing is only enabled with `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE`
+//! set, so the feature costs nothing in production unless asked for. With the
+//! `serde` feature the frames are part of the JSON output.
+
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::sync::Arc;
+
+/// One frame of a backtrace, see `DebugError::backtrace_frames`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame 
+{
+    /// Demangled function name, `<unknown>` if it couldn't be resolved
+    pub function: String,
+    /// Source file, if debug info is available
+    pub file: Option<String>,
+    /// Line in the source file, if debug info is available
+    pub line: Option<u32>,
+}
+
+/// Captures a backtrace, `None` if capturing is disabled or unsupported
+pub(crate) fn capture() -> Option<Arc<Backtrace>> 
+{
+    let backtrace = Backtrace::capture();
+    match backtrace.status() {
+        BacktraceStatus::Captured => Some(Arc::new(backtrace)),
+        _ => None,
+    }
+}
+
+/// Extracts the frames of a backtrace, starting at the frame that created the error
+///
+/// `std` doesn't expose the frames on stable, so they are parsed from the `Display`
+/// form, which prints one "N: function" line per frame followed by an optional
+/// "at file:line:column" line. Leading frames inside this crate are skipped.
+pub(crate) fn frames(backtrace: &Backtrace) -> Vec<Frame> 
+{
+    let mut frames: Vec<Frame> = Vec::new();
+    for line in backtrace.to_string().lines() {
+        let line = line.trim();
+        if let Some(location) = line.strip_prefix("at ") {
+            if let Some(frame) = frames.last_mut() {
+                let mut parts = location.rsplitn(3, ':');
+                let _column = parts.next();
+                frame.line = parts.next().and_then(|line| line.parse().ok());
+                frame.file = parts.next().map(str::to_string);
+            }
+            continue;
+        }
+        // Inlined functions are printed without an index below the frame they are in
+        let function = match line.split_once(": ") {
+            Some((index, function)) if index.chars().all(|c| c.is_ascii_digit()) => function,
+            _ => line,
+        };
+        if !function.is_empty() {
+            frames.push(Frame { function: function.to_string(), file: None, line: None });
+        }
+    }
+    let internal = frames
+        .iter()
+        .take_while(|frame| frame.function.starts_with("debug_error::") || frame.function.starts_with("<debug_error::"))
+        .count();
+    frames.split_off(internal)
+}
@@ -0,0 +1,124 @@
+//! Recent log history attached to errors, enabled with the `breadcrumbs` feature
+//!
+//! `BreadcrumbLogger` wraps the logger of the application and keeps the last records
+//! in a bounded buffer. Every `DebugError` created afterwards gets a snapshot of that
+//! buffer, so you can see what happened right before the error.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use ::log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+static BUFFER: Mutex<RingBuffer> = Mutex::new(RingBuffer::new(0));
+
+/// Bounded buffer which drops the oldest entry when it is full
+#[derive(Debug)]
+struct RingBuffer 
+{
+    entries: VecDeque<String>,
+    capacity: usize,
+}
+
+impl RingBuffer 
+{
+    const fn new(capacity: usize) -> Self 
+    {
+        Self { entries: VecDeque::new(), capacity }
+    }
+
+    fn push(&mut self, entry: String) 
+    {
+        if self.capacity == 0 {
+            return;
+        }
+        while self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    fn set_capacity(&mut self, capacity: usize) 
+    {
+        self.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+    }
+}
+
+/// Logger that records breadcrumbs and forwards every record to an inner logger
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error, BreadcrumbLogger};
+/// use log::{info, warn, LevelFilter};
+///
+/// let inner = env_logger::Builder::new().filter_level(LevelFilter::Info).build();
+/// BreadcrumbLogger::new(inner, 2).init(LevelFilter::Info).unwrap();
+///
+/// info!("loading config");
+/// info!("connecting to db");
+/// warn!("retrying connection");
+///
+/// let err = debug_error!("Database unreachable");
+/// assert_eq!(err.breadcrumbs().len(), 2);
+/// assert!(err.breadcrumbs()[0].ends_with("connecting to db"));
+/// assert!(err.breadcrumbs()[1].starts_with("WARN"));
+/// assert!(format!("{:#}", err).contains("breadcrumbs:"));
+/// ```
+pub struct BreadcrumbLogger 
+{
+    inner: Box<dyn Log>,
+}
+
+impl BreadcrumbLogger 
+{
+    /// Wraps `inner`, keeping the last `capacity` records as breadcrumbs
+    pub fn new(inner: impl Log + 'static, capacity: usize) -> Self 
+    {
+        lock_buffer().set_capacity(capacity);
+        Self { inner: Box::new(inner) }
+    }
+
+    /// Installs this logger as the global logger
+    pub fn init(self, max_level: LevelFilter) -> Result<(), SetLoggerError> 
+    {
+        ::log::set_boxed_logger(Box::new(self))?;
+        ::log::set_max_level(max_level);
+        Ok(())
+    }
+}
+
+impl Log for BreadcrumbLogger 
+{
+    fn enabled(&self, _metadata: &Metadata) -> bool 
+    {
+        // Records are kept as breadcrumbs even if the inner logger filters them out
+        true
+    }
+
+    fn log(&self, record: &Record) 
+    {
+        lock_buffer().push(format!("{} {}: {}", record.level(), record.target(), record.args()));
+        if self.inner.enabled(record.metadata()) {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) 
+    {
+        self.inner.flush();
+    }
+}
+
+fn lock_buffer() -> std::sync::MutexGuard<'static, RingBuffer> 
+{
+    BUFFER.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Returns a copy of the current breadcrumbs, oldest first
+pub(crate) fn snapshot() -> Vec<String> 
+{
+    lock_buffer().entries.iter().cloned().collect()
+}
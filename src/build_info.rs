@@ -0,0 +1,22 @@
+//! The git commit the crate was built from, for mapping production errors to source
+
+/// Returns the git commit hash captured at build time
+///
+/// The hash is taken from the `GIT_HASH` environment variable if set, otherwise from
+/// `git rev-parse --short HEAD`. Builds without git, like from a source tarball,
+/// return `"unknown"`. The hash is included as `build` in the `{:#}` form and in JSON.
+///
+/// # Examples
+///
+/// ```rust
+/// let build = debug_error::build_info();
+///
+/// assert!(!build.is_empty());
+/// ```
+pub fn build_info() -> &'static str 
+{
+    match option_env!("DEBUG_ERROR_GIT_HASH") {
+        Some(hash) => hash,
+        None => "unknown",
+    }
+}
@@ -0,0 +1,57 @@
+//! Forwarding of new errors to a channel, e.g. for an error panel in a GUI
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::RwLock;
+
+use crate::DebugError;
+
+static ERROR_CHANNEL: RwLock<Option<(u64, Sender<DebugError>)>> = RwLock::new(None);
+static CHANNEL_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Sends a clone of every error created from now on to the channel
+///
+/// Meant for desktop apps that show errors in the UI: the UI thread drains the
+/// receiver in its event loop. Errors of all threads are sent, with and without
+/// logging. Once the receiver is dropped the channel is disabled again.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error, set_error_channel};
+/// use std::sync::mpsc;
+///
+/// let (sender, receiver) = mpsc::channel();
+/// set_error_channel(sender);
+///
+/// std::thread::spawn(|| debug_error!("Sync with server failed")).join().unwrap();
+/// let _ = debug_error!("Theme file missing");
+///
+/// // The UI thread drains the channel on every frame
+/// let messages: Vec<String> = receiver.try_iter().map(|err| err.message.into_owned()).collect();
+/// assert_eq!(messages, ["Sync with server failed", "Theme file missing"]);
+/// ```
+pub fn set_error_channel(sender: Sender<DebugError>) 
+{
+    let generation = CHANNEL_GENERATION.fetch_add(1, Ordering::Relaxed);
+    *ERROR_CHANNEL.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some((generation, sender));
+}
+
+/// Sends a new error to the channel, if one is set
+pub(crate) fn send_created(err: &DebugError) 
+{
+    let channel = ERROR_CHANNEL.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let Some((generation, sender)) = channel.as_ref() else {
+        return;
+    };
+    if sender.send(err.clone()).is_err() {
+        // The receiver is gone, stop cloning errors nobody reads. Another thread may
+        // have set a new channel since the read lock was released, that one stays.
+        let failed = *generation;
+        drop(channel);
+        let mut channel = ERROR_CHANNEL.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if channel.as_ref().is_some_and(|(generation, _)| *generation == failed) {
+            channel.take();
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Results that complain when they are dropped without being handled
+//!
+//! `#[must_use]` only catches ignored results at compile time. `CheckedResult` also
+//! catches results that were stored and then silently dropped, by logging a warning
+//! (or panicking with the `checked-panic` feature) that points at where it was created.
+
+use std::panic::Location;
+
+use crate::DebugError;
+
+/// Wrapper around `Result<T, DebugError>` that must be handled before it is dropped
+///
+/// A result counts as handled once it is consumed with `into_result`, `unwrap`,
+/// `expect` or `handled`. The `?` operator can't be implemented for custom types on
+/// stable Rust, so use `checked.into_result()?` instead.
+///
+/// # Examples
+///
+#[cfg_attr(not(feature = "checked-panic"), doc = "```rust")]
+#[cfg_attr(feature = "checked-panic", doc = "```rust,should_panic")]
+/// use debug_error::{debug_error, CheckedResult, DebugError};
+/// # debug_error::__capture_logs();
+///
+/// fn flush_cache() -> CheckedResult<()> {
+///     CheckedResult::new(Err(debug_error!("Cache flush failed")))
+/// }
+///
+/// fn run() -> Result<(), DebugError> {
+///     // Handled: `into_result` hands the result over to `?`
+///     flush_cache().into_result().or_else(|_| Ok::<(), DebugError>(()))?;
+///     flush_cache().handled();
+///     Ok(())
+/// }
+///
+/// run().unwrap();
+/// assert!(debug_error::__captured_logs().is_empty());
+///
+/// // Unhandled: dropping the result logs a warning
+/// let _ = flush_cache();
+/// let records = debug_error::__captured_logs();
+/// assert_eq!(records.len(), 1);
+/// assert_eq!(records[0].level, log::Level::Warn);
+/// assert!(records[0].message.contains("dropped without being handled"));
+/// assert!(records[0].message.contains("Cache flush failed"));
+/// ```
+#[must_use = "a CheckedResult warns when it is dropped without being handled"]
+#[derive(Debug)]
+pub struct CheckedResult<T> 
+{
+    result: Option<Result<T, DebugError>>,
+    location: &'static Location<'static>,
+}
+
+impl<T> CheckedResult<T> 
+{
+    /// Wraps a result, remembering the caller location for the drop warning
+    #[track_caller]
+    pub fn new(result: Result<T, DebugError>) -> Self 
+    {
+        Self { result: Some(result), location: Location::caller() }
+    }
+
+    /// Returns true if the wrapped result is `Ok`, without handling it
+    pub fn is_ok(&self) -> bool 
+    {
+        matches!(self.result, Some(Ok(_)))
+    }
+
+    /// Returns true if the wrapped result is `Err`, without handling it
+    pub fn is_err(&self) -> bool 
+    {
+        matches!(self.result, Some(Err(_)))
+    }
+
+    /// Handles the result by returning it, e.g. to use it with `?`
+    pub fn into_result(mut self) -> Result<T, DebugError> 
+    {
+        self.take()
+    }
+
+    /// Marks the result as handled and discards it
+    pub fn handled(mut self) 
+    {
+        self.result = None;
+    }
+
+    /// Handles the result by returning the `Ok` value, panics on `Err`
+    #[track_caller]
+    pub fn unwrap(mut self) -> T 
+    {
+        match self.take() {
+            Ok(value) => value,
+            Err(err) => panic!("called `CheckedResult::unwrap()` on an `Err` value: {}", err),
+        }
+    }
+
+    /// Handles the result by returning the `Ok` value, panics with `msg` on `Err`
+    #[track_caller]
+    pub fn expect(mut self, msg: &str) -> T 
+    {
+        match self.take() {
+            Ok(value) => value,
+            Err(err) => panic!("{}: {}", msg, err),
+        }
+    }
+
+    fn take(&mut self) -> Result<T, DebugError> 
+    {
+        self.result.take().expect("CheckedResult is only emptied when consumed")
+    }
+}
+
+impl<T> Drop for CheckedResult<T> 
+{
+    fn drop(&mut self) 
+    {
+        let Some(result) = self.result.take() else {
+            return;
+        };
+        let outcome = match &result {
+            Ok(_) => "Ok".to_string(),
+            Err(err) => format!("Err: {}", err),
+        };
+        let message = format!(
+            "CheckedResult created at {} was dropped without being handled ({})",
+            self.location, outcome
+        );
+        // Never panic while unwinding, that would abort the process
+        if cfg!(feature = "checked-panic") && !std::thread::panicking() {
+            panic!("{}", message);
+        }
+        ::log::warn!("{}", message);
+    }
+}
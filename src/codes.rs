@@ -0,0 +1,213 @@
+//! Error codes with a per-module prefix, like `DB-001` or `HTTP-042`
+//!
+//! `define_error_prefix!` declares the prefix of a module once and
+//! `debug_error_coded!` combines it with a number. The code is built at compile time,
+//! so it is a `&'static str` like any other code.
+//!
+//! Alternatively, `declare_error_codes!` declares all codes centrally as constants,
+//! which `debug_error!(code = ..., "...")` accepts. With the `strict-codes` feature
+//! that form rejects raw string literals, so a typo in a code is a compile error.
+
+/// Returns the number of decimal digits of a number
+const fn digits(mut number: u32) -> usize 
+{
+    let mut digits = 1;
+    while number >= 10 {
+        number /= 10;
+        digits += 1;
+    }
+    digits
+}
+
+/// Returns the length of `<prefix>-<number>` with the number padded to three digits
+#[doc(hidden)]
+pub const fn __coded_len(prefix: &str, number: u32) -> usize 
+{
+    let digits = digits(number);
+    prefix.len() + 1 + if digits < 3 { 3 } else { digits }
+}
+
+/// Writes `<prefix>-<number>` with the number padded to three digits
+#[doc(hidden)]
+pub const fn __coded_bytes<const N: usize>(prefix: &str, mut number: u32) -> [u8; N] 
+{
+    let mut bytes = [b'0'; N];
+    let prefix = prefix.as_bytes();
+    let mut i = 0;
+    while i < prefix.len() {
+        bytes[i] = prefix[i];
+        i += 1;
+    }
+    bytes[prefix.len()] = b'-';
+    // Write the digits from the end, the padding zeros are already in place
+    let mut position = N;
+    while position > prefix.len() + 1 {
+        position -= 1;
+        bytes[position] = b'0' + (number % 10) as u8;
+        number /= 10;
+    }
+    bytes
+}
+
+/// Sets the error code prefix of the current module for `debug_error_coded!`
+///
+/// The prefix is stored in a private module-level constant, so it is scoped to the
+/// module the macro is called in. Child modules don't inherit it: they either call
+/// `define_error_prefix!` themselves or import it with
+/// `use super::__DEBUG_ERROR_PREFIX;`.
+///
+/// # Examples
+///
+/// ```rust
+/// mod db {
+///     debug_error::define_error_prefix!("DB");
+///
+///     pub fn connect() -> debug_error::DebugError {
+///         debug_error::debug_error_coded!(1, "Connection refused")
+///     }
+/// }
+///
+/// assert_eq!(db::connect().code(), Some("DB-001"));
+/// ```
+#[macro_export]
+macro_rules! define_error_prefix
+{
+    ($prefix:expr) => {
+        #[allow(dead_code)]
+        const __DEBUG_ERROR_PREFIX: &str = $prefix;
+    };
+}
+
+/// Creates a DebugError with a code made of the module prefix and a number
+///
+/// The number is zero padded to three digits, `debug_error_coded!(42, "...")` in a
+/// module with the prefix `HTTP` gets the code `HTTP-042`. The number has to be a
+/// constant, because the code is built at compile time.
+///
+/// # Examples
+///
+/// ```rust
+/// debug_error::define_error_prefix!("HTTP");
+///
+/// let err = debug_error::debug_error_coded!(42, "Upstream returned {}", 502);
+/// assert_eq!(err.code(), Some("HTTP-042"));
+/// assert_eq!(err.message, "Upstream returned 502");
+///
+/// assert_eq!(debug_error::debug_error_coded!(1234, "Overflow").code(), Some("HTTP-1234"));
+/// ```
+#[macro_export]
+macro_rules! debug_error_coded
+{
+    ($number:expr, $($arg:tt)*) => {{
+        const PREFIX: &str = __DEBUG_ERROR_PREFIX;
+        const NUMBER: u32 = $number;
+        const LEN: usize = $crate::__coded_len(PREFIX, NUMBER);
+        const BYTES: [u8; LEN] = $crate::__coded_bytes::<LEN>(PREFIX, NUMBER);
+        const CODE: &str = match ::core::str::from_utf8(&BYTES) {
+            Ok(code) => code,
+            Err(_) => panic!("error code prefix must be valid UTF-8"),
+        };
+        $crate::debug_error!($($arg)*).with_code(CODE)
+    }};
+}
+
+/// An error code declared with `declare_error_codes!`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ErrorCode(&'static str);
+
+impl ErrorCode 
+{
+    pub const fn new(code: &'static str) -> Self 
+    {
+        Self(code)
+    }
+
+    pub const fn as_str(&self) -> &'static str 
+    {
+        self.0
+    }
+}
+
+impl std::fmt::Display for ErrorCode 
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result 
+    {
+        f.write_str(self.0)
+    }
+}
+
+/// Values accepted by `debug_error!(code = ..., "...")`
+///
+/// Implemented for `ErrorCode` and, unless the `strict-codes` feature is enabled, for
+/// string literals.
+pub trait IntoErrorCode 
+{
+    fn into_error_code(self) -> &'static str;
+}
+
+impl IntoErrorCode for ErrorCode 
+{
+    fn into_error_code(self) -> &'static str 
+    {
+        self.0
+    }
+}
+
+#[cfg(not(feature = "strict-codes"))]
+impl IntoErrorCode for &'static str 
+{
+    fn into_error_code(self) -> &'static str 
+    {
+        self
+    }
+}
+
+/// Declares error codes as constants, to use them with `debug_error!(code = ..., "...")`
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error, declare_error_codes};
+///
+/// declare_error_codes! {
+///     /// The database didn't answer in time
+///     E_DB_TIMEOUT = "DB-001",
+///     E_HTTP_BAD_GATEWAY = "HTTP-502",
+/// }
+///
+/// let err = debug_error!(code = E_DB_TIMEOUT, "Query on {} timed out", "users");
+/// assert_eq!(err.code(), Some("DB-001"));
+/// assert_eq!(err.message, "Query on users timed out");
+/// assert_eq!(E_HTTP_BAD_GATEWAY.as_str(), "HTTP-502");
+/// ```
+///
+/// A typo in a code doesn't compile:
+///
+/// ```rust,compile_fail
+/// use debug_error::{debug_error, declare_error_codes};
+///
+/// declare_error_codes! {
+///     E_DB_TIMEOUT = "DB-001",
+/// }
+///
+/// let err = debug_error!(code = E_DB_TIMOUT, "Query timed out");
+/// ```
+///
+/// With the `strict-codes` feature, raw string codes are rejected as well:
+///
+#[cfg_attr(feature = "strict-codes", doc = "```rust,compile_fail")]
+#[cfg_attr(not(feature = "strict-codes"), doc = "```rust")]
+/// use debug_error::debug_error;
+///
+/// let err = debug_error!(code = "DB-001", "Query timed out");
+/// ```
+#[macro_export]
+macro_rules! declare_error_codes
+{
+    ($($(#[$meta:meta])* $name:ident = $code:literal),* $(,)?) => {
+        $(
+            $(#[$meta])*
+            pub const $name: $crate::ErrorCode = $crate::ErrorCode::new($code);
+        )*
+    };
+}
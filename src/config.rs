@@ -0,0 +1,308 @@
+//! Global settings that change how errors are rendered and logged
+//!
+//! Settings are stored in statics, so they apply to the whole process. Set them once
+//! at startup, before errors are created.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::RwLock;
+
+static SHOW_COLUMN: AtomicBool = AtomicBool::new(true);
+static CODE_DOC_BASE: RwLock<Option<String>> = RwLock::new(None);
+static LOCATION_SEPARATOR: RwLock<&'static str> = RwLock::new(" at ");
+static PATH_TRIM: RwLock<Option<String>> = RwLock::new(None);
+static MAX_CHAIN_DEPTH: AtomicUsize = AtomicUsize::new(32);
+
+/// Sets whether locations are rendered as `file:line:column` or just `file:line`
+///
+/// Applies to `Display` and to the message logged by `debug_error_with_log`.
+/// Columns are shown by default.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error, set_show_column};
+///
+/// let err = debug_error!("Timeout");
+/// let with_column = format!("Timeout at {}:{}:{}", err.location.file(), err.location.line(), err.location.column());
+/// assert_eq!(err.to_string(), with_column);
+///
+/// set_show_column(false);
+/// let without_column = format!("Timeout at {}:{}", err.location.file(), err.location.line());
+/// assert_eq!(err.to_string(), without_column);
+/// ```
+pub fn set_show_column(show: bool) 
+{
+    SHOW_COLUMN.store(show, Ordering::Relaxed);
+}
+
+/// Returns whether locations include the column number
+pub fn show_column() -> bool 
+{
+    SHOW_COLUMN.load(Ordering::Relaxed)
+}
+
+/// Sets the separator between message and location, `" at "` by default
+///
+/// Applies to `Display`, to the message logged by `debug_error_with_log` and to the
+/// chain renderings like `render_tree` and `flatten`. Some log formats prefer `" @ "`,
+/// or `"\n"` to put the location on its own line.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error, set_location_separator};
+///
+/// let err = debug_error!("Timeout");
+/// assert_eq!(err.to_string(), format!("Timeout at {}", err.location));
+///
+/// set_location_separator(" @ ");
+/// assert_eq!(err.to_string(), format!("Timeout @ {}", err.location));
+///
+/// let outer = debug_error!("Sync failed").with_source(err.clone());
+/// assert_eq!(outer.render_tree(), format!("Sync failed @ {}\n└─ Timeout @ {}", outer.location, err.location));
+/// assert_eq!(outer.flatten(), format!("Sync failed @ {} caused by: Timeout @ {}", outer.location, err.location));
+/// ```
+pub fn set_location_separator(sep: &'static str) 
+{
+    *LOCATION_SEPARATOR.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = sep;
+}
+
+/// Returns the separator set with `set_location_separator`
+pub fn location_separator() -> &'static str 
+{
+    *LOCATION_SEPARATOR.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Sets a prefix that is cut from file paths when locations are rendered
+///
+/// Applies to `Display` of locations, so to `Display` of errors and to the message
+/// logged by `debug_error_with_log`. Useful when errors carry absolute paths, e.g. from
+/// a build with `--remap-path-prefix` unset. `LocationInfo::file` and `originated_in`
+/// keep seeing the full path. Paths that don't start with the prefix are left alone.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{set_path_trim, DebugError};
+///
+/// let err = DebugError::from_parts("Timeout".to_string(), "/build/app/src/db.rs".to_string(), 12, 5);
+/// assert_eq!(err.location.to_string(), "/build/app/src/db.rs:12:5");
+///
+/// set_path_trim("/build/app/");
+/// assert_eq!(err.location.to_string(), "src/db.rs:12:5");
+/// assert_eq!(err.location.file(), "/build/app/src/db.rs");
+/// ```
+pub fn set_path_trim(prefix: &str) 
+{
+    *PATH_TRIM.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(prefix.to_string());
+}
+
+/// Returns the prefix set with `set_path_trim`
+pub fn path_trim() -> Option<String> 
+{
+    PATH_TRIM.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+}
+
+/// Cuts the prefix set with `set_path_trim` from a file path
+pub(crate) fn trim_path(file: &str) -> &str 
+{
+    let trim = PATH_TRIM.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+    match trim.as_deref() {
+        Some(prefix) => file.strip_prefix(prefix).unwrap_or(file),
+        None => file,
+    }
+}
+
+/// Sets the maximum length of a source chain, counting the outermost error, 32 by default
+///
+/// Guards against code that wraps an error in a loop. Attaching a source with
+/// `with_source`, `wrap_err!` or the context helpers drops the oldest causes beyond
+/// the limit, so the chain stays at `depth` errors. Only `DebugError` sources can be
+/// cut, chains of other error types are kept as they are. A depth of 0 counts as 1.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error, set_max_chain_depth};
+///
+/// set_max_chain_depth(10);
+///
+/// let mut err = debug_error!("Attempt 0 failed");
+/// for attempt in 1..100 {
+///     err = debug_error!("Attempt {} failed", attempt).with_source(err);
+/// }
+///
+/// let chain: Vec<String> = err.chain().map(|err| err.to_string()).collect();
+/// assert_eq!(chain.len(), 10);
+/// assert!(chain[0].starts_with("Attempt 99 failed"));
+/// assert!(chain[9].starts_with("Attempt 90 failed"));
+/// ```
+pub fn set_max_chain_depth(depth: usize) 
+{
+    MAX_CHAIN_DEPTH.store(depth.max(1), Ordering::Relaxed);
+}
+
+/// Returns the maximum source chain length set with `set_max_chain_depth`
+pub fn max_chain_depth() -> usize 
+{
+    MAX_CHAIN_DEPTH.load(Ordering::Relaxed)
+}
+
+/// Sets the base URL of the documentation pages of error codes
+///
+/// An error with a code then links to its page, the base followed by the code, like
+/// `rustc --explain` for compiler errors. The link is rendered as a `see:` line in the
+/// `{:#}` form and included as `doc_url` in JSON. Errors without a code get no link.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error, set_code_doc_base};
+///
+/// set_code_doc_base("https://docs.example.com/errors/");
+///
+/// let err = debug_error!("Invalid license key").with_code("E1234");
+/// assert_eq!(err.doc_url().as_deref(), Some("https://docs.example.com/errors/E1234"));
+/// assert!(format!("{:#}", err).contains("\n  see: https://docs.example.com/errors/E1234"));
+/// # #[cfg(feature = "serde")]
+/// assert!(err.to_json().contains(r#""doc_url":"https://docs.example.com/errors/E1234""#));
+///
+/// let err = debug_error!("Invalid license key");
+/// assert_eq!(err.doc_url(), None);
+/// assert!(!format!("{:#}", err).contains("see:"));
+/// ```
+pub fn set_code_doc_base(base: &str) 
+{
+    *CODE_DOC_BASE.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(base.to_string());
+}
+
+/// Returns the base URL set with `set_code_doc_base`
+pub fn code_doc_base() -> Option<String> 
+{
+    CODE_DOC_BASE.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+}
+
+/// Applies settings from `DEBUG_ERROR_*` environment variables
+///
+/// Centralizes the configuration of twelve-factor apps: call it once at startup,
+/// after installing the logger. Unset variables leave their setting alone. If any
+/// variable has an invalid value, nothing is applied and the error names the variable.
+///
+/// | Variable | Setting | Value |
+/// |---|---|---|
+/// | `DEBUG_ERROR_LOG_LEVEL` | `log::set_max_level` | `off`, `error`, `warn`, `info`, `debug` or `trace` |
+/// | `DEBUG_ERROR_SHOW_COLUMN` | `set_show_column` | `true`/`false`, `1`/`0`, `yes`/`no` or `on`/`off` |
+/// | `DEBUG_ERROR_PATH_TRIM` | `set_path_trim` | Path prefix, e.g. `/build/app/` |
+/// | `DEBUG_ERROR_SUPPRESSED_CODES` | `set_suppressed_codes` | Comma separated codes, e.g. `DB-001,DB-002` |
+/// | `DEBUG_ERROR_MAX_LOGGED` | `set_max_logged_errors` | Number of errors |
+/// | `DEBUG_ERROR_CODE_DOC_BASE` | `set_code_doc_base` | Base URL |
+/// | `DEBUG_ERROR_CAPTURED_ENV_VARS` | `set_captured_env_vars` (`env-snapshot` feature) | Comma separated names |
+/// | `DEBUG_ERROR_SENTRY_AUTOCAPTURE` | `set_sentry_autocapture` (`sentry` feature) | Like `DEBUG_ERROR_SHOW_COLUMN` |
+/// | `DEBUG_ERROR_OTEL_AUTORECORD` | `set_otel_autorecord` (`otel` feature) | Like `DEBUG_ERROR_SHOW_COLUMN` |
+///
+/// Values are trimmed and case-insensitive, except for codes, names, the path prefix
+/// and the URL.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{code_doc_base, init_from_env, path_trim, show_column};
+///
+/// # use debug_error::__set_env_var as set_var;
+/// set_var("DEBUG_ERROR_LOG_LEVEL", "warn");
+/// set_var("DEBUG_ERROR_SHOW_COLUMN", "false");
+/// set_var("DEBUG_ERROR_PATH_TRIM", "/build/app/");
+/// set_var("DEBUG_ERROR_CODE_DOC_BASE", "https://docs.example.com/errors/");
+///
+/// init_from_env().unwrap();
+///
+/// assert_eq!(log::max_level(), log::LevelFilter::Warn);
+/// assert!(!show_column());
+/// assert_eq!(path_trim().as_deref(), Some("/build/app/"));
+/// assert_eq!(code_doc_base().as_deref(), Some("https://docs.example.com/errors/"));
+///
+/// set_var("DEBUG_ERROR_MAX_LOGGED", "lots");
+/// let err = init_from_env().unwrap_err();
+/// assert_eq!(err.message, "Invalid value \"lots\" for DEBUG_ERROR_MAX_LOGGED, expected a number");
+/// ```
+#[track_caller]
+pub fn init_from_env() -> Result<(), crate::DebugError> 
+{
+    let location = std::panic::Location::caller();
+    let invalid = |var: &str, value: &str, expected: &str| {
+        crate::DebugError::new(format!("Invalid value {:?} for {}, expected {}", value, var, expected), location)
+    };
+    let parse_bool = |var: &str| match std::env::var(var) {
+        Err(_) => Ok(None),
+        Ok(value) => match value.trim().to_ascii_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => Ok(Some(true)),
+            "0" | "false" | "no" | "off" => Ok(Some(false)),
+            _ => Err(invalid(var, &value, "true or false")),
+        },
+    };
+    let list = |var: &str| {
+        std::env::var(var).ok().map(|value| {
+            value.split(',').map(|item| item.trim().to_string()).filter(|item| !item.is_empty()).collect::<Vec<_>>()
+        })
+    };
+
+    // Parse everything first, so an invalid value doesn't leave the settings half applied
+    let log_level = match std::env::var("DEBUG_ERROR_LOG_LEVEL") {
+        Err(_) => None,
+        Ok(value) => match value.trim().parse::<::log::LevelFilter>() {
+            Ok(level) => Some(level),
+            Err(_) => return Err(invalid("DEBUG_ERROR_LOG_LEVEL", &value, "off, error, warn, info, debug or trace")),
+        },
+    };
+    let show_column = parse_bool("DEBUG_ERROR_SHOW_COLUMN")?;
+    let path_trim = std::env::var("DEBUG_ERROR_PATH_TRIM").ok();
+    let suppressed_codes = list("DEBUG_ERROR_SUPPRESSED_CODES");
+    let max_logged = match std::env::var("DEBUG_ERROR_MAX_LOGGED") {
+        Err(_) => None,
+        Ok(value) => match value.trim().parse::<usize>() {
+            Ok(max) => Some(max),
+            Err(_) => return Err(invalid("DEBUG_ERROR_MAX_LOGGED", &value, "a number")),
+        },
+    };
+    let code_doc_base = std::env::var("DEBUG_ERROR_CODE_DOC_BASE").ok();
+    #[cfg(feature = "env-snapshot")]
+    let captured_env_vars = list("DEBUG_ERROR_CAPTURED_ENV_VARS");
+    #[cfg(feature = "sentry")]
+    let sentry_autocapture = parse_bool("DEBUG_ERROR_SENTRY_AUTOCAPTURE")?;
+    #[cfg(feature = "otel")]
+    let otel_autorecord = parse_bool("DEBUG_ERROR_OTEL_AUTORECORD")?;
+
+    if let Some(level) = log_level {
+        ::log::set_max_level(level);
+    }
+    if let Some(show) = show_column {
+        set_show_column(show);
+    }
+    if let Some(prefix) = path_trim {
+        set_path_trim(&prefix);
+    }
+    if let Some(codes) = suppressed_codes {
+        let codes: Vec<&str> = codes.iter().map(String::as_str).collect();
+        crate::logging::set_suppressed_codes(&codes);
+    }
+    if let Some(max) = max_logged {
+        crate::logging::set_max_logged_errors(max);
+    }
+    if let Some(base) = code_doc_base {
+        set_code_doc_base(&base);
+    }
+    #[cfg(feature = "env-snapshot")]
+    if let Some(vars) = captured_env_vars {
+        let vars: Vec<&str> = vars.iter().map(String::as_str).collect();
+        crate::env_snapshot::set_captured_env_vars(&vars);
+    }
+    #[cfg(feature = "sentry")]
+    if let Some(enabled) = sentry_autocapture {
+        crate::sentry::set_sentry_autocapture(enabled);
+    }
+    #[cfg(feature = "otel")]
+    if let Some(enabled) = otel_autorecord {
+        crate::otel::set_otel_autorecord(enabled);
+    }
+    Ok(())
+}
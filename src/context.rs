@@ -0,0 +1,129 @@
+//! Scoped context that is attached to every error created while it is active
+//!
+//! Call `error_context` at the top of a function and keep the returned guard alive.
+//! Every `DebugError` created on the same thread while the guard exists records the
+//! context, so you get a breadcrumb trail without passing context to each error.
+
+use std::cell::RefCell;
+use std::marker::PhantomData;
+
+thread_local! {
+    static CONTEXT_STACK: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Guard returned by `error_context`, removes its context again when dropped
+///
+/// The guard is bound to the thread it was created on, because the context stack
+/// is thread-local.
+#[must_use = "the context is removed as soon as the guard is dropped"]
+#[derive(Debug)]
+pub struct ContextGuard 
+{
+    depth: usize,
+    _not_send: PhantomData<*const ()>,
+}
+
+/// Pushes a context string that is attached to errors until the guard is dropped
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error, error_context};
+///
+/// fn load_user(id: u64) -> debug_error::DebugError {
+///     let _g = error_context(format!("processing user {}", id));
+///     let _inner = error_context("loading profile");
+///     debug_error!("profile missing")
+/// }
+///
+/// let err = load_user(5);
+/// assert_eq!(err.context(), ["processing user 5", "loading profile"]);
+/// assert!(err.to_string().contains("(context: processing user 5 > loading profile)"));
+///
+/// // The guards are gone, so new errors don't carry the context anymore
+/// assert!(debug_error!("other").context().is_empty());
+/// ```
+pub fn error_context(context: impl Into<String>) -> ContextGuard 
+{
+    let depth = CONTEXT_STACK.with(|stack| {
+        let mut stack = stack.borrow_mut();
+        stack.push(context.into());
+        stack.len() - 1
+    });
+    ContextGuard { depth, _not_send: PhantomData }
+}
+
+impl Drop for ContextGuard 
+{
+    fn drop(&mut self) 
+    {
+        // Truncate instead of pop, so guards dropped out of order can't leave stale entries
+        CONTEXT_STACK.with(|stack| stack.borrow_mut().truncate(self.depth));
+    }
+}
+
+/// Returns a snapshot of the context stack of the current thread
+pub(crate) fn current_context() -> Vec<String> 
+{
+    CONTEXT_STACK.with(|stack| stack.borrow().clone())
+}
+
+/// Context of a thread, captured with `capture_context` to install it on another one
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CapturedContext 
+{
+    entries: Vec<String>,
+}
+
+/// Captures the context of the current thread, e.g. to hand it to a worker thread
+///
+/// Thread-local context is lost when spawning a thread. Capture it before spawning and
+/// install it in the worker with `CapturedContext::install_on_current`, then errors
+/// created in the worker carry the context of the parent.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{capture_context, debug_error, error_context};
+///
+/// let _trace = error_context("trace 4bf92f35");
+/// let ctx = capture_context();
+///
+/// let err = std::thread::spawn(move || {
+///     let _ctx = ctx.install_on_current();
+///     let _job = error_context("resizing image");
+///     debug_error!("Unsupported format")
+/// })
+/// .join()
+/// .unwrap();
+///
+/// assert_eq!(err.context(), ["trace 4bf92f35", "resizing image"]);
+/// ```
+pub fn capture_context() -> CapturedContext 
+{
+    CapturedContext { entries: current_context() }
+}
+
+impl CapturedContext 
+{
+    /// Pushes the captured context onto the context of the current thread
+    ///
+    /// Works like `error_context` for each entry: the context is removed again when the
+    /// returned guard is dropped.
+    pub fn install_on_current(&self) -> ContextGuard 
+    {
+        let depth = CONTEXT_STACK.with(|stack| {
+            let mut stack = stack.borrow_mut();
+            let depth = stack.len();
+            stack.extend(self.entries.iter().cloned());
+            depth
+        });
+        ContextGuard { depth, _not_send: PhantomData }
+    }
+
+    /// Returns the captured context entries, outermost first
+    pub fn entries(&self) -> &[String] 
+    {
+        &self.entries
+    }
+}
@@ -0,0 +1,51 @@
+//! Environment snapshot at error time, enabled with the `env-snapshot` feature
+//!
+//! Only variables that were explicitly whitelisted with `set_captured_env_vars` are
+//! captured, so secrets in the environment don't end up in error reports.
+
+use std::sync::RwLock;
+
+static CAPTURED_VARS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Sets the environment variables that are captured into every new error
+///
+/// Variables that aren't set when the error is created are skipped. The current
+/// working directory is always captured as `cwd`.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error, set_captured_env_vars};
+///
+/// # use debug_error::__set_env_var as set_var;
+/// set_var("APP_ENV", "staging");
+/// set_var("APP_SECRET", "hunter2");
+/// set_captured_env_vars(&["APP_ENV", "RUST_LOG_UNSET_FOR_TEST"]);
+///
+/// let err = debug_error!("Startup failed");
+/// assert!(err.env().contains(&("APP_ENV".to_string(), "staging".to_string())));
+/// assert!(err.env().iter().any(|(key, _)| key == "cwd"));
+/// assert!(!err.env().iter().any(|(key, _)| key == "APP_SECRET"));
+/// assert!(format!("{:#}", err).contains("APP_ENV=staging"));
+/// ```
+pub fn set_captured_env_vars(vars: &[&str]) 
+{
+    let mut captured = CAPTURED_VARS.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+    *captured = vars.iter().map(|var| var.to_string()).collect();
+}
+
+/// Captures the current working directory and the whitelisted variables
+pub(crate) fn capture() -> Vec<(String, String)> 
+{
+    let mut env = Vec::new();
+    if let Ok(cwd) = std::env::current_dir() {
+        env.push(("cwd".to_string(), cwd.display().to_string()));
+    }
+    let captured = CAPTURED_VARS.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+    for var in captured.iter() {
+        if let Ok(value) = std::env::var(var) {
+            env.push((var.clone(), value));
+        }
+    }
+    env
+}
@@ -13,7 +13,7 @@
 //! 
 //! # Quick Start
 //!
-//! ```rust
+//! ```rust,no_run
 //! use debug_error::{DebugError, debug_error, debug_error_with_log};
 //! use log::info;
 //!
@@ -42,11 +42,164 @@
 //!     true
 //! }
 //! ```
+//!
+//! # Locations
+//!
+//! Every macro and function that creates an error records the location in the
+//! calling code, never one inside this crate: the macros expand at the call site and
+//! the functions and extension methods are `#[track_caller]`. Helpers of your own
+//! that create errors can be marked `#[track_caller]` too, then errors point at
+//! their callers.
+//!
+//! ```rust
+//! use debug_error::*;
+//! use std::sync::Mutex;
+//!
+//! // Creates an error and notes the file and line, all on one line
+//! macro_rules! here {
+//!     () => { (file!().to_string(), line!()) };
+//! }
+//! fn at(err: &DebugError) -> (String, u32) {
+//!     (err.location.file().to_string(), err.location.line())
+//! }
+//! fn parse(text: &str) -> Result<u32, std::num::ParseIntError> {
+//!     text.parse()
+//! }
+//!
+//! // Macros
+//! let (err, here) = (debug_error!("m"), here!());
+//! assert_eq!(at(&err), here);
+//! let (err, here) = (debug_error_with_log!("m"), here!());
+//! assert_eq!(at(&err), here);
+//! #[cfg(debug_assertions)]
+//! {
+//!     let (err, here) = (debug_only_error!("m").unwrap(), here!());
+//!     assert_eq!(at(&err), here);
+//! }
+//! let (err, here) = (debug_error_versioned!("m"), here!());
+//! assert_eq!(at(&err), here);
+//! define_error_prefix!("LOC");
+//! let (err, here) = (debug_error_coded!(1, "m"), here!());
+//! assert_eq!(at(&err), here);
+//! let (err, here) = (wrap_err!(std::io::Error::other("io"), "m"), here!());
+//! assert_eq!(at(&err), here);
+//! let ((err, _), here) = (debug_error_pair!("m"), here!());
+//! assert_eq!(at(&err), here);
+//! let (err, here) = (debug_marker!().inflate("m"), here!());
+//! assert_eq!(at(&err), here);
+//! let (err, here) = ((|| -> Result<(), DebugError> { check!(1 > 2); Ok(()) })().unwrap_err(), here!());
+//! assert_eq!(at(&err), here);
+//! let (err, here) = ((|| -> Result<(), DebugError> { http_bail!(404, "m") })().unwrap_err(), here!());
+//! assert_eq!(at(&err), here);
+//! let (err, here) = (try_block!("m", { Err(std::io::Error::other("io"))?; Ok(()) }).unwrap_err(), here!());
+//! assert_eq!(at(&err), here);
+//!
+//! // Conversions with `?`
+//! let (err, here) = ((|| -> Result<(), DebugError> { Err(std::io::Error::other("io"))? })().unwrap_err(), here!());
+//! assert_eq!(at(&err), here);
+//! let boxed = || -> Result<(), Box<dyn std::error::Error + Send + Sync>> { Err("boxed".into()) };
+//! let (err, here) = ((|| -> Result<(), DebugError> { Ok(boxed()?) })().unwrap_err(), here!());
+//! assert_eq!(at(&err), here);
+//!
+//! // Extension methods
+//! let (err, here) = (parse("x").kind_err(Kind::InvalidInput).unwrap_err(), here!());
+//! assert_eq!(at(&err), here);
+//! let (err, here) = (parse("x").context("m").unwrap_err(), here!());
+//! assert_eq!(at(&err), here);
+//! let (err, here) = (parse("x").context_with(|| "m".to_string()).unwrap_err(), here!());
+//! assert_eq!(at(&err), here);
+//! let (err, here) = (parse("x").contexts(&["inner", "outer"]).unwrap_err(), here!());
+//! assert_eq!(at(&err), here);
+//!
+//! // Functions
+//! let mutex = Mutex::new(0);
+//! let _ = std::panic::catch_unwind(|| {
+//!     let _guard = mutex.lock().unwrap();
+//!     panic!("poison");
+//! });
+//! let (err, here) = (lock_err(mutex.lock(), "m").unwrap_err(), here!());
+//! assert_eq!(at(&err), here);
+//! let (err, here) = (catch(|| panic!("m")).unwrap_err(), here!());
+//! assert_eq!(at(&err), here);
+//! debug_error::__set_env_var("DEBUG_ERROR_MAX_LOGGED", "many");
+//! let (err, here) = (init_from_env().unwrap_err(), here!());
+//! assert_eq!(at(&err), here);
+//! ```
+
+use std::any::Any;
+use std::borrow::Cow;
+use std::error::Error;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::context::current_context;
+use crate::location::LocationInfo;
+
 #[derive(Debug, Clone)]
 pub struct DebugError 
 {
-    pub message: String,
-    pub location: &'static std::panic::Location<'static>,
+    /// Borrowed for literal messages of the macros, so creating them doesn't allocate
+    pub message: Cow<'static, str>,
+    pub location: LocationInfo,
+    details: Box<Details>,
+}
+
+/// Optional data of a `DebugError`
+///
+/// Kept behind a `Box` so `Result<T, DebugError>` stays small, no matter how many
+/// optional details an error supports.
+#[derive(Debug, Clone, Default)]
+struct Details 
+{
+    seq: u64,
+    original_location: Option<LocationInfo>,
+    source: Option<Arc<dyn Error + Send + Sync + 'static>>,
+    causes: Vec<DebugError>,
+    context: Vec<String>,
+    attachments: Vec<(&'static str, Arc<dyn Any + Send + Sync>)>,
+    level: Option<::log::Level>,
+    code: Option<&'static str>,
+    kind: Option<crate::kind::Kind>,
+    category: Option<&'static str>,
+    os_code: Option<i32>,
+    http_status: Option<u16>,
+    http_request: Option<(String, String)>,
+    crate_version: Option<&'static str>,
+    suggestions: Vec<String>,
+    kv: Vec<(&'static str, String)>,
+    expected: bool,
+    user_error: bool,
+    /// Module of the macro call that created the error, the target of its log records
+    module_path: Option<&'static str>,
+    retryable: Option<bool>,
+    attempt: Option<(u32, u32)>,
+    logged: LoggedFlag,
+    #[cfg(feature = "track-propagation")]
+    propagation: Vec<LocationInfo>,
+    #[cfg(feature = "env-snapshot")]
+    env: Vec<(String, String)>,
+    #[cfg(feature = "breadcrumbs")]
+    breadcrumbs: Vec<String>,
+    #[cfg(feature = "resource-snapshot")]
+    resources: Option<crate::resources::ResourceSnapshot>,
+    #[cfg(feature = "backtrace")]
+    backtrace: Option<Arc<std::backtrace::Backtrace>>,
+    #[cfg(feature = "timestamp")]
+    timestamp: Option<std::time::SystemTime>,
+}
+
+/// Whether an error was logged already, see `DebugError::mark_logged`
+///
+/// Atomic so `DebugError` stays `Sync`, clones start with the value of the original.
+#[derive(Debug, Default)]
+struct LoggedFlag(AtomicBool);
+
+impl Clone for LoggedFlag 
+{
+    fn clone(&self) -> Self 
+    {
+        LoggedFlag(AtomicBool::new(self.0.load(Ordering::Relaxed)))
+    }
 }
 
 /// An error type that captures the location where it was created
@@ -55,21 +208,1682 @@ pub struct DebugError
 /// making debugging much easier by showing exactly where errors originate.
 impl DebugError 
 {
-    pub fn new(message: String, location: &'static std::panic::Location<'static>) -> Self 
+    pub fn new(message: impl Into<Cow<'static, str>>, location: &'static std::panic::Location<'static>) -> Self 
+    {
+        Self::with_location_info(message.into(), LocationInfo::from(location))
+    }
+
+    /// Creates an error from pre-built format arguments
+    ///
+    /// Lets logging bridges and other macros hand `format_args!` output straight to
+    /// `DebugError` without going through `debug_error!`. Arguments without any
+    /// placeholders are copied as is instead of being run through the formatter.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::DebugError;
+    /// use std::panic::Location;
+    ///
+    /// let user = "alice";
+    /// let err = DebugError::from_args(format_args!("User {} not found", user), Location::caller());
+    ///
+    /// assert_eq!(err.message, "User alice not found");
+    /// assert_eq!(err.location.line(), line!() - 3);
+    /// ```
+    pub fn from_args(args: std::fmt::Arguments, location: &'static std::panic::Location<'static>) -> Self 
+    {
+        Self::new(__message(args), location)
+    }
+
+    /// Rebuilds an error from its parts, e.g. after receiving it from another service
+    ///
+    /// The location is taken as given instead of being captured, so the error keeps
+    /// pointing at the place it originally came from.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{debug_error, DebugError};
+    ///
+    /// // Sender: send the parts over the wire
+    /// let err = debug_error!("Payment declined");
+    /// let wire = format!("{}|{}|{}|{}", err.message, err.location.file(), err.location.line(), err.location.column());
+    ///
+    /// // Receiver: rebuild the error from the parts
+    /// let parts: Vec<&str> = wire.split('|').collect();
+    /// let rebuilt = DebugError::from_parts(
+    ///     parts[0].to_string(),
+    ///     parts[1].to_string(),
+    ///     parts[2].parse().unwrap(),
+    ///     parts[3].parse().unwrap(),
+    /// );
+    ///
+    /// assert_eq!(rebuilt.to_string(), err.to_string());
+    /// assert_eq!(rebuilt.location, err.location);
+    /// ```
+    pub fn from_parts(message: String, file: String, line: u32, column: u32) -> Self 
+    {
+        Self::with_location_info(message.into(), LocationInfo::new(file, line, column))
+    }
+
+    fn with_location_info(message: Cow<'static, str>, location: LocationInfo) -> Self 
+    {
+        let details = Details {
+            seq: crate::stats::next_seq(),
+            context: current_context(),
+            #[cfg(feature = "env-snapshot")]
+            env: crate::env_snapshot::capture(),
+            #[cfg(feature = "breadcrumbs")]
+            breadcrumbs: crate::breadcrumbs::snapshot(),
+            #[cfg(feature = "resource-snapshot")]
+            resources: crate::resources::capture(),
+            #[cfg(feature = "backtrace")]
+            backtrace: crate::backtrace::capture(),
+            #[cfg(feature = "timestamp")]
+            timestamp: Some(std::time::SystemTime::now()),
+            ..Default::default()
+        };
+        let err = Self { message, location, details: Box::new(details) };
+        crate::stats::count_created();
+        crate::channel::send_created(&err);
+        crate::subscriber::notify(&err);
+        #[cfg(feature = "sentry")]
+        if crate::sentry::sentry_autocapture() {
+            err.capture();
+        }
+        err
+    }
+
+    /// Returns the sequence number of the error, counting up from 0 per process
+    ///
+    /// Every new error gets the next number, on whichever thread it is created, so
+    /// sorting by `seq` gives the exact order of creation even when timestamps
+    /// collide. Clones and errors derived with `with_message` keep the number.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    ///
+    /// let errors: Vec<_> = (0..3).map(|i| debug_error!("Failure {}", i)).collect();
+    /// let from_thread = std::thread::spawn(|| debug_error!("Worker failure")).join().unwrap();
+    ///
+    /// assert!(errors[0].seq() < errors[1].seq());
+    /// assert!(errors[1].seq() < errors[2].seq());
+    /// assert!(errors[2].seq() < from_thread.seq());
+    /// assert_eq!(errors[0].clone().seq(), errors[0].seq());
+    /// ```
+    pub fn seq(&self) -> u64 
+    {
+        self.details.seq
+    }
+
+    /// Sets the error that caused this one
+    ///
+    /// The source is stored behind an `Arc`, so cloning a `DebugError` shares the
+    /// chain instead of copying it. Sources are never mutated after being attached,
+    /// which makes the shared chain behave like a deep copy: every clone renders the
+    /// same messages and `downcast_ref` on a cause keeps working. The tradeoff is
+    /// that sources must be `Send + Sync + 'static`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{debug_error, DebugError};
+    ///
+    /// let io_err = std::io::Error::new(std::io::ErrorKind::NotFound, "config.toml missing");
+    /// let err = debug_error!("Failed to load config").with_source(io_err);
+    /// let cloned = err.clone();
+    ///
+    /// let chain: Vec<String> = cloned.chain().map(|e| e.to_string()).collect();
+    /// assert_eq!(chain.len(), 2);
+    /// assert_eq!(chain[1], "config.toml missing");
+    /// assert!(cloned.chain().nth(1).unwrap().downcast_ref::<std::io::Error>().is_some());
+    /// ```
+    pub fn with_source<E>(mut self, source: E) -> Self 
+    where
+        E: Error + Send + Sync + 'static,
     {
-        Self { message, location }
+        self.set_source(Arc::new(source));
+        self
+    }
+
+    /// Attaches a source, cutting the chain at `max_chain_depth`
+    ///
+    /// Errors created without a macro take the module of a `DebugError` source.
+    fn set_source(&mut self, source: Arc<dyn Error + Send + Sync + 'static>) 
+    {
+        if self.details.module_path.is_none() {
+            let source_err = (&*source as &(dyn Error + 'static)).downcast_ref::<DebugError>();
+            self.details.module_path = source_err.and_then(|source_err| source_err.details.module_path);
+        }
+        self.details.source = truncate_chain(source, crate::config::max_chain_depth() - 1);
+    }
+
+    /// Adds independent causes, e.g. the failures of parallel tasks
+    ///
+    /// Unlike the single `source`, an error can have any number of causes. They are
+    /// rendered as a "caused by multiple:" list in the `{:#}` form, and `chain` visits
+    /// them depth-first after the source chain.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    ///
+    /// let eu = debug_error!("eu-west replica timed out");
+    /// let us = debug_error!("us-east replica refused connection");
+    /// let err = debug_error!("All replicas failed").with_causes(vec![eu, us]);
+    ///
+    /// let rendered = format!("{:#}", err);
+    /// assert!(rendered.contains("\n  caused by multiple:\n    - eu-west replica timed out at "));
+    /// assert!(rendered.contains("\n    - us-east replica refused connection at "));
+    ///
+    /// let chain: Vec<String> = err.chain().map(|level| level.to_string()).collect();
+    /// assert_eq!(chain.len(), 3);
+    /// assert!(chain[1].starts_with("eu-west replica timed out"));
+    /// assert!(chain[2].starts_with("us-east replica refused connection"));
+    /// ```
+    pub fn with_causes(mut self, causes: Vec<DebugError>) -> Self 
+    {
+        self.details.causes.extend(causes);
+        self
+    }
+
+    /// Returns the causes added with `with_causes`
+    pub fn causes(&self) -> &[DebugError] 
+    {
+        &self.details.causes
+    }
+
+    /// Replaces the message, keeping the location and the previous error as the source
+    ///
+    /// Useful for putting a friendly message on top of the technical one: the error
+    /// still points at its origin, and the original message stays available through
+    /// `chain()` for logging. The new error keeps the level, code and kind, so it is
+    /// logged and handled the same way. All other details, like context, key-value
+    /// pairs and causes, stay with the previous error only.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{debug_error, DebugError};
+    ///
+    /// let technical = debug_error!("SQLSTATE 23505: duplicate key value violates unique constraint")
+    ///     .with_code("DB-023")
+    ///     .with_kv("table", "users");
+    /// let location = technical.location.clone();
+    ///
+    /// let friendly = technical.with_message("This email address is already registered");
+    /// assert_eq!(friendly.message, "This email address is already registered");
+    /// assert_eq!(friendly.location, location);
+    /// assert_eq!(friendly.code(), Some("DB-023"));
+    /// assert!(friendly.kv().is_empty());
+    ///
+    /// let previous = friendly.chain().nth(1).unwrap().downcast_ref::<DebugError>().unwrap();
+    /// assert!(previous.message.starts_with("SQLSTATE 23505"));
+    /// assert_eq!(previous.kv(), [("table", "users".to_string())]);
+    /// assert_eq!(friendly.chain().count(), 2);
+    /// ```
+    pub fn with_message(self, message: impl Into<String>) -> Self 
+    {
+        let details = Details {
+            seq: self.details.seq,
+            original_location: self.details.original_location.clone(),
+            level: self.details.level,
+            code: self.details.code,
+            kind: self.details.kind,
+            module_path: self.details.module_path,
+            ..Details::default()
+        };
+        let mut err = DebugError { message: Cow::Owned(message.into()), location: self.location.clone(), details: Box::new(details) };
+        err.set_source(Arc::new(self));
+        err
+    }
+
+    /// Attaches an arbitrary value to the error under the given name
+    ///
+    /// Attachments carry diagnostic data that isn't a string, like a captured request
+    /// buffer or a state snapshot. They aren't rendered in `Display`, handlers retrieve
+    /// them with `attachment`.
+    ///
+    /// Attachments are stored behind an `Arc` so `DebugError` stays `Clone`. A clone
+    /// shares the attached values with the original instead of copying them, which is
+    /// why values must be `Send + Sync` and can't be mutated after attaching.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    ///
+    /// let err = debug_error!("Malformed request")
+    ///     .attach("body", vec![0xde_u8, 0xad, 0xbe, 0xef])
+    ///     .attach("status", 400_u16);
+    ///
+    /// let cloned = err.clone();
+    /// assert_eq!(cloned.attachment::<Vec<u8>>("body"), Some(&vec![0xde, 0xad, 0xbe, 0xef]));
+    /// assert_eq!(cloned.attachment::<u16>("status"), Some(&400));
+    /// // Wrong type or unknown name
+    /// assert_eq!(cloned.attachment::<u32>("status"), None);
+    /// assert_eq!(cloned.attachment::<u16>("missing"), None);
+    /// ```
+    pub fn attach<T: Any + Send + Sync>(mut self, name: &'static str, value: T) -> Self 
+    {
+        self.details.attachments.push((name, Arc::new(value)));
+        self
+    }
+
+    /// Returns the attachment with the given name if it has type `T`
+    pub fn attachment<T: Any>(&self, name: &str) -> Option<&T> 
+    {
+        self.details
+            .attachments
+            .iter()
+            .filter(|(attachment_name, _)| *attachment_name == name)
+            .find_map(|(_, value)| value.downcast_ref::<T>())
+    }
+
+    /// Replaces the displayed location of the error
+    ///
+    /// Meant for generated code and macros that create errors on behalf of user code,
+    /// where the captured location would point at the generator instead of the user's
+    /// source. The captured location is still available with `original_location`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    ///
+    /// let err = debug_error!("Unknown field `nmae`")
+    ///     .with_location_override("src/models/user.rs", 12, 5);
+    ///
+    /// assert_eq!(err.to_string(), "Unknown field `nmae` at src/models/user.rs:12:5");
+    /// assert_ne!(err.original_location().unwrap().file(), "src/models/user.rs");
+    /// ```
+    pub fn with_location_override(mut self, file: &'static str, line: u32, col: u32) -> Self 
+    {
+        let captured = std::mem::replace(&mut self.location, LocationInfo::new(file, line, col));
+        // Keep the location that was really captured, even when overriding twice
+        self.details.original_location.get_or_insert(captured);
+        self
+    }
+
+    /// Returns the captured location if it was replaced with `with_location_override`
+    pub fn original_location(&self) -> Option<&LocationInfo> 
+    {
+        self.details.original_location.as_ref()
+    }
+
+    /// Sets the kind of the error, see `Kind`
+    pub fn with_kind(mut self, kind: crate::kind::Kind) -> Self 
+    {
+        self.details.kind = Some(kind);
+        self
+    }
+
+    /// Returns the kind set with `with_kind`
+    pub fn kind(&self) -> Option<crate::kind::Kind> 
+    {
+        self.details.kind
+    }
+
+    /// Sets the subsystem the error belongs to, e.g. `"db"`
+    ///
+    /// `debug_error_with_log!` logs errors of a category to the target mapped with
+    /// `set_category_target_map`. The macros set it with the `category = ...` form.
+    pub fn with_category(mut self, category: &'static str) -> Self 
+    {
+        self.details.category = Some(category);
+        self
+    }
+
+    /// Returns the category set with `with_category`
+    pub fn category(&self) -> Option<&'static str> 
+    {
+        self.details.category
+    }
+
+    /// Sets the HTTP status to respond with, see `http_bail!`
+    pub fn with_http_status(mut self, status: u16) -> Self 
+    {
+        self.details.http_status = Some(status);
+        self
+    }
+
+    /// Returns the HTTP status set with `with_http_status`
+    pub fn http_status(&self) -> Option<u16> 
+    {
+        self.details.http_status
+    }
+
+    /// Records the method and path of the HTTP request the error happened in
+    ///
+    /// Rendered together with the status of `with_http_status` in the alternate form
+    /// `{:#}`, and included as `http` in JSON. Middleware can stamp all errors of a
+    /// request with it, e.g. in `map_err` around the handler.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    ///
+    /// let err = debug_error!("Order 7 not found").with_http_context("GET", "/orders/7").with_http_status(404);
+    ///
+    /// assert_eq!(err.http_context(), Some(("GET", "/orders/7")));
+    /// assert!(format!("{:#}", err).contains("\n  http: GET /orders/7 (status 404)"));
+    /// assert!(!err.to_string().contains("/orders/7"));
+    /// ```
+    pub fn with_http_context(mut self, method: &str, path: &str) -> Self 
+    {
+        self.details.http_request = Some((method.to_string(), path.to_string()));
+        self
+    }
+
+    /// Returns the method and path set with `with_http_context`
+    pub fn http_context(&self) -> Option<(&str, &str)> 
+    {
+        self.details.http_request.as_ref().map(|(method, path)| (method.as_str(), path.as_str()))
+    }
+
+    /// Sets the version of the crate the error was created in, see `debug_error_versioned!`
+    pub fn with_crate_version(mut self, version: &'static str) -> Self 
+    {
+        self.details.crate_version = Some(version);
+        self
+    }
+
+    /// Returns the version set with `with_crate_version`
+    pub fn crate_version(&self) -> Option<&'static str> 
+    {
+        self.details.crate_version
+    }
+
+    /// Sets a machine readable code for the error, e.g. `"DB-001"`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    ///
+    /// let err = debug_error!("Connection pool exhausted").with_code("DB-002");
+    /// assert_eq!(err.code(), Some("DB-002"));
+    /// assert_eq!(debug_error!("No code").code(), None);
+    /// ```
+    pub fn with_code(mut self, code: &'static str) -> Self 
+    {
+        self.details.code = Some(code);
+        self
+    }
+
+    /// Returns the code set with `with_code`
+    pub fn code(&self) -> Option<&'static str> 
+    {
+        self.details.code
+    }
+
+    /// Returns the documentation URL of the code, see `set_code_doc_base`
+    pub fn doc_url(&self) -> Option<String> 
+    {
+        let code = self.details.code?;
+        crate::config::code_doc_base().map(|base| format!("{}{}", base, code))
+    }
+
+    /// Maps the error with a closure, e.g. into a domain error enum at a module boundary
+    ///
+    /// Just `f(self)`, but reads well at the call site and keeps the mapping in one place
+    /// when it looks at the code, the kind and the message together.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{debug_error, Kind};
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// enum StoreError {
+    ///     Missing,
+    ///     Other(String),
+    /// }
+    ///
+    /// let err = debug_error!("Blob 9f2c not found").with_kind(Kind::NotFound);
+    /// let store_err = err.classify(|err| match err.kind() {
+    ///     Some(Kind::NotFound) => StoreError::Missing,
+    ///     _ => StoreError::Other(err.message.to_string()),
+    /// });
+    /// assert_eq!(store_err, StoreError::Missing);
+    /// ```
+    pub fn classify<T, F>(&self, f: F) -> T 
+    where
+        F: Fn(&DebugError) -> T,
+    {
+        f(self)
+    }
+
+    /// Looks up the code in a table of `(code, value)` arms, for table-driven dispatch
+    ///
+    /// Returns the value of the first arm with the error's code, `None` if no arm
+    /// matches or the error has no code.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// enum Handler {
+    ///     Retry,
+    ///     ReAuthenticate,
+    ///     Alert,
+    /// }
+    ///
+    /// let arms = [("DB-TIMEOUT", Handler::Retry), ("AUTH-EXPIRED", Handler::ReAuthenticate), ("DISK-FULL", Handler::Alert)];
+    ///
+    /// let handler = |code: &'static str| debug_error!("Request failed").with_code(code).match_code(&arms);
+    /// assert_eq!(handler("DB-TIMEOUT"), Some(&Handler::Retry));
+    /// assert_eq!(handler("AUTH-EXPIRED"), Some(&Handler::ReAuthenticate));
+    /// assert_eq!(handler("DISK-FULL"), Some(&Handler::Alert));
+    /// assert_eq!(handler("HTTP-404"), None);
+    /// assert_eq!(debug_error!("No code").match_code(&arms), None);
+    /// ```
+    pub fn match_code<'a, T>(&self, arms: &'a [(&str, T)]) -> Option<&'a T> 
+    {
+        let code = self.details.code?;
+        arms.iter().find(|(arm, _)| *arm == code).map(|(_, value)| value)
+    }
+
+    /// Returns true if this error or any `DebugError` in its source chain has the code
+    ///
+    /// Sources of other error types are skipped, they have no code.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    ///
+    /// let timeout = debug_error!("Socket read timed out").with_code("TIMEOUT");
+    /// let query = debug_error!("Query failed").with_code("DB-001").with_source(timeout);
+    /// let err = debug_error!("Loading page failed").with_source(query);
+    ///
+    /// assert!(err.chain_has_code("TIMEOUT"));
+    /// assert!(err.chain_has_code("DB-001"));
+    /// assert!(!err.chain_has_code("HTTP-404"));
+    /// ```
+    pub fn chain_has_code(&self, code: &str) -> bool 
+    {
+        self.chain()
+            .filter_map(|err| err.downcast_ref::<DebugError>())
+            .any(|err| err.code() == Some(code))
+    }
+
+    /// Returns a stable fingerprint for grouping errors in trackers like Sentry
+    ///
+    /// The fingerprint is derived from the code if set, otherwise from the file and
+    /// line, and never from the message: every error raised at the same site groups
+    /// together, whatever values were formatted into it. Unlike `std::hash::Hash`, which
+    /// is meant for in-memory dedup and whose default hasher is seeded per process, the
+    /// fingerprint is a fixed FNV-1a hash, identical across processes, machines and
+    /// releases of this crate.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    ///
+    /// fn load(id: u32) -> debug_error::DebugError {
+    ///     debug_error!("User {} not found", id)
+    /// }
+    ///
+    /// assert_eq!(load(1).fingerprint(), load(2).fingerprint());
+    /// assert_eq!(load(1).fingerprint().len(), 16);
+    /// assert_ne!(load(1).fingerprint(), debug_error!("User 1 not found").fingerprint());
+    ///
+    /// // Coded errors group by code, wherever they are created
+    /// assert_eq!(
+    ///     debug_error!("Timeout").with_code("DB-001").fingerprint(),
+    ///     debug_error!("Timed out").with_code("DB-001").fingerprint()
+    /// );
+    /// ```
+    pub fn fingerprint(&self) -> String 
+    {
+        let key = match self.details.code {
+            Some(code) => format!("code:{}", code),
+            None => format!("{}:{}", self.location.file(), self.location.line()),
+        };
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for byte in key.bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        format!("{:016x}", hash)
+    }
+
+    /// Adds a possible fix, can be called several times
+    ///
+    /// Suggestions are rendered as a numbered list in the alternate form `{:#}`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    ///
+    /// let err = debug_error!("Port 8080 is already in use")
+    ///     .suggest("stop the other process using the port")
+    ///     .suggest("set a different port with --port")
+    ///     .suggest("set PORT in the environment");
+    ///
+    /// let rendered = format!("{:#}", err);
+    /// assert!(rendered.contains(
+    ///     "\n  suggestions:\
+    ///      \n    1. stop the other process using the port\
+    ///      \n    2. set a different port with --port\
+    ///      \n    3. set PORT in the environment"
+    /// ));
+    /// assert_eq!(err.suggestions().len(), 3);
+    /// ```
+    pub fn suggest(mut self, suggestion: impl Into<String>) -> Self 
+    {
+        self.details.suggestions.push(suggestion.into());
+        self
+    }
+
+    /// Adds a structured key-value pair, can be called several times
+    ///
+    /// Pairs are kept in the order they were added and rendered in the alternate form
+    /// `{:#}`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    ///
+    /// let err = debug_error!("Payment declined")
+    ///     .with_kv("order_id", 1832)
+    ///     .with_kv("provider", "stripe");
+    ///
+    /// assert_eq!(err.kv(), [("order_id", "1832".to_string()), ("provider", "stripe".to_string())]);
+    /// assert!(format!("{:#}", err).contains("\n  fields: order_id=1832, provider=stripe"));
+    /// ```
+    pub fn with_kv(mut self, key: &'static str, value: impl ToString) -> Self 
+    {
+        self.details.kv.push((key, value.to_string()));
+        self
+    }
+
+    /// Returns the key-value pairs added with `with_kv`
+    pub fn kv(&self) -> &[(&'static str, String)] 
+    {
+        &self.details.kv
+    }
+
+    /// Returns the suggestions added with `suggest`
+    pub fn suggestions(&self) -> &[String] 
+    {
+        &self.details.suggestions
+    }
+
+    /// Returns a message that is safe to show to users or return from an API
+    ///
+    /// Never contains the internal message, file paths or sources, only the code if
+    /// one is set. Keep using `Display` for logs, which shows the full detail.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    ///
+    /// let err = debug_error!("Query on table users failed: password column missing").with_code("DB-001");
+    /// assert_eq!(err.public_message(), "An error occurred (DB-001)");
+    /// assert!(!err.public_message().contains(err.location.file()));
+    ///
+    /// let uncoded = debug_error!("/srv/app/secrets.toml not readable");
+    /// assert_eq!(uncoded.public_message(), "An internal error occurred");
+    /// ```
+    pub fn public_message(&self) -> String 
+    {
+        match self.details.code {
+            Some(code) => format!("An error occurred ({})", code),
+            None => "An internal error occurred".to_string(),
+        }
+    }
+
+    /// Splits the message into a title and a detail, e.g. for notifications
+    ///
+    /// The title is everything before the first `". "` or newline, whichever comes
+    /// first, and the detail is the rest, without the separator and leading
+    /// whitespace. Without a separator the whole message is the title and the detail
+    /// is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    ///
+    /// let err = debug_error!("Backup failed. The disk /dev/sdb1 is full. Free up space and retry.");
+    /// assert_eq!(err.title_and_detail(), ("Backup failed", "The disk /dev/sdb1 is full. Free up space and retry."));
+    ///
+    /// let err = debug_error!("Sync paused\n3 files conflict with remote changes");
+    /// assert_eq!(err.title_and_detail(), ("Sync paused", "3 files conflict with remote changes"));
+    ///
+    /// let err = debug_error!("Network unreachable");
+    /// assert_eq!(err.title_and_detail(), ("Network unreachable", ""));
+    /// ```
+    pub fn title_and_detail(&self) -> (&str, &str) 
+    {
+        let sentence_end = self.message.find(". ").map(|index| (index, 2));
+        let line_end = self.message.find('\n').map(|index| (index, 1));
+        let split = match (sentence_end, line_end) {
+            (Some(sentence), Some(line)) => Some(sentence.min(line)),
+            (sentence, line) => sentence.or(line),
+        };
+        match split {
+            Some((index, len)) => (&self.message[..index], self.message[index + len..].trim_start()),
+            None => (&self.message, ""),
+        }
+    }
+
+    /// Returns the raw OS error code of a converted `io::Error`, e.g. `13` for EACCES
+    pub fn os_code(&self) -> Option<i32> 
+    {
+        self.details.os_code
+    }
+
+    /// Sets the level the error is logged at by helpers like `ResultExt::ignore_logged`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    /// use log::Level;
+    ///
+    /// assert_eq!(debug_error!("Disk full").level(), Level::Error);
+    /// assert_eq!(debug_error!("Cache stale").with_level(Level::Warn).level(), Level::Warn);
+    /// ```
+    pub fn with_level(mut self, level: ::log::Level) -> Self 
+    {
+        self.details.level = Some(level);
+        self
+    }
+
+    /// Returns the level the error is logged at
+    ///
+    /// That is the level set with `with_level`, otherwise `Debug` for expected errors,
+    /// `Info` for user errors and `Error` for all others.
+    pub fn level(&self) -> ::log::Level 
+    {
+        match self.details.level {
+            Some(level) => level,
+            None if self.details.expected => ::log::Level::Debug,
+            None if self.details.user_error => ::log::Level::Info,
+            None => ::log::Level::Error,
+        }
+    }
+
+    /// Marks the error as part of the normal flow, like a 404 for an unknown id
+    ///
+    /// Expected errors are logged at debug level instead of error level, so they don't
+    /// trigger alerts meant for bugs.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{debug_error, DebugError, ResultExt};
+    /// # debug_error::__capture_logs();
+    ///
+    /// let not_found: Result<(), DebugError> = Err(debug_error!("User 7 not found").expected());
+    /// let crashed: Result<(), DebugError> = Err(debug_error!("Worker crashed"));
+    ///
+    /// not_found.ignore_logged();
+    /// crashed.ignore_logged();
+    ///
+    /// let records = debug_error::__captured_logs();
+    /// assert_eq!(records[0].level, log::Level::Debug);
+    /// assert_eq!(records[1].level, log::Level::Error);
+    /// ```
+    pub fn expected(mut self) -> Self 
+    {
+        self.details.expected = true;
+        self
+    }
+
+    /// Marks the error as caused by bad input of the caller, not by a bug
+    ///
+    /// User errors are logged at info level instead of error level, so they don't page
+    /// anyone. Unlike `expected`, which marks a normal outcome like a cache miss, a user
+    /// error is always at least partly the fault of the caller. Start
+    /// `debug_error_with_log!` with `user_error,` to mark the error before it's logged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{debug_error, debug_error_with_log};
+    /// # debug_error::__capture_logs();
+    ///
+    /// let bad_date = "2024-13-01";
+    /// let err = debug_error_with_log!(user_error, "Invalid date {:?}", bad_date);
+    /// assert!(err.is_user_error());
+    /// debug_error_with_log!("Date parser crashed");
+    ///
+    /// let records = debug_error::__captured_logs();
+    /// assert_eq!(records[0].level, log::Level::Info);
+    /// assert!(records[0].message.contains("Invalid date \"2024-13-01\""));
+    /// assert_eq!(records[1].level, log::Level::Error);
+    ///
+    /// assert!(debug_error!("Unknown currency").user_error().is_user_error());
+    /// assert!(!debug_error!("Unknown currency").is_user_error());
+    /// ```
+    pub fn user_error(mut self) -> Self 
+    {
+        self.details.user_error = true;
+        self
+    }
+
+    /// Returns true if the error was marked with `user_error`
+    pub fn is_user_error(&self) -> bool 
+    {
+        self.details.user_error
+    }
+
+    /// Sets whether retrying the failed operation may help, overriding the kind
+    pub fn with_retryable(mut self, retryable: bool) -> Self 
+    {
+        self.details.retryable = Some(retryable);
+        self
+    }
+
+    /// Returns true if retrying the failed operation may help
+    ///
+    /// That is the value set with `with_retryable`, otherwise true for the kinds
+    /// `Timeout` and `Unavailable`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{debug_error, Kind};
+    ///
+    /// assert!(debug_error!("Broker unreachable").with_kind(Kind::Unavailable).is_retryable());
+    /// assert!(!debug_error!("Malformed id").with_kind(Kind::InvalidInput).is_retryable());
+    /// assert!(debug_error!("Lock contended").with_retryable(true).is_retryable());
+    /// assert!(!debug_error!("Unknown failure").is_retryable());
+    /// ```
+    pub fn is_retryable(&self) -> bool 
+    {
+        match self.details.retryable {
+            Some(retryable) => retryable,
+            None => matches!(self.details.kind, Some(crate::kind::Kind::Timeout | crate::kind::Kind::Unavailable)),
+        }
+    }
+
+    /// Records that the error happened on attempt `current` of `max`
+    ///
+    /// Set by `retry` and `retry_backoff` on the errors they return, and rendered as
+    /// `(attempt 3/3)` after the message.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    ///
+    /// let err = debug_error!("Upload failed").with_attempt(2, 5);
+    /// assert_eq!(err.attempt(), Some((2, 5)));
+    /// assert!(err.to_string().starts_with("Upload failed (attempt 2/5) at "));
+    /// ```
+    pub fn with_attempt(mut self, current: u32, max: u32) -> Self 
+    {
+        self.details.attempt = Some((current, max));
+        self
+    }
+
+    /// Returns the attempt set with `with_attempt`, as `(current, max)`
+    pub fn attempt(&self) -> Option<(u32, u32)> 
+    {
+        self.details.attempt
+    }
+
+    /// Returns true if the error was marked with `expected`
+    pub fn is_expected(&self) -> bool 
+    {
+        self.details.expected
+    }
+
+    /// Records the caller location as a propagation hop
+    ///
+    /// A `DebugError` returned through `?` without conversion goes through the
+    /// identity `From<T> for T` impl of the standard library, which can't be
+    /// specialized, so `?` alone can't record where an error passed through. Call
+    /// this method (or `ResultExt::propagate`) right before `?` instead.
+    ///
+    /// Hops are only recorded with the `track-propagation` feature, without it this
+    /// method does nothing and costs nothing.
+    #[track_caller]
+    pub fn propagated(#[allow(unused_mut)] mut self) -> Self 
+    {
+        #[cfg(feature = "track-propagation")]
+        self.details.propagation.push(LocationInfo::from(std::panic::Location::caller()));
+        self
+    }
+
+    /// Returns the locations recorded by `propagated`, in the order the error passed them
+    ///
+    /// Always empty without the `track-propagation` feature.
+    pub fn propagation(&self) -> &[LocationInfo] 
+    {
+        #[cfg(feature = "track-propagation")]
+        return &self.details.propagation;
+        #[cfg(not(feature = "track-propagation"))]
+        return &[];
+    }
+
+    /// Logs the error at error level with both its origin and the current location
+    ///
+    /// A plain `error!("{}", err)` record points at the log statement, which is easy
+    /// to confuse with the place the error was created. This method logs
+    /// "error from A, logged at B" so both locations are visible. It always logs, also
+    /// errors that were logged already, e.g. by `debug_error_with_log!`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    /// # debug_error::__capture_logs();
+    ///
+    /// let err = debug_error!("Cache miss for key {}", 42);
+    /// err.log_self(); let logged_line = line!();
+    ///
+    /// // Error: Cache miss for key 42 at src/main.rs:3:11 (logged at src/main.rs:4:5)
+    /// let records = debug_error::__captured_logs();
+    /// assert_eq!(records[0].level, log::Level::Error);
+    /// assert!(records[0].message.starts_with(&format!("Error: Cache miss for key 42 at {}", err.location)));
+    /// assert!(records[0].message.contains(&format!(" (logged at {}:{}:", file!(), logged_line)));
+    /// ```
+    #[track_caller]
+    pub fn log_self(&self) 
+    {
+        ::log::error!("Error: {} (logged at {})", self, std::panic::Location::caller());
+        self.mark_logged();
+    }
+
+    /// Returns true if the error was logged already
+    ///
+    /// Set by `debug_error_with_log!` and the logging helpers like
+    /// `ResultExt::ignore_logged`, which skip errors that were logged before. That way
+    /// an error logged deep down isn't logged a second time by a top-level handler.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{debug_error, debug_error_with_log, DebugError, ResultExt};
+    /// # debug_error::__capture_logs();
+    ///
+    /// fn read_sensor() -> Result<f64, DebugError> {
+    ///     Err(debug_error_with_log!("Sensor offline"))
+    /// }
+    ///
+    /// // The top-level handler logs too, but the error was logged already
+    /// let err = read_sensor().unwrap_err();
+    /// assert!(err.is_logged());
+    /// Err::<f64, _>(err).ignore_logged();
+    /// assert_eq!(debug_error::__captured_logs().len(), 1);
+    ///
+    /// // Errors logged by other means can be marked by hand
+    /// let err = debug_error!("Disk almost full");
+    /// err.mark_logged();
+    /// Err::<f64, _>(err).ignore_logged();
+    /// assert_eq!(debug_error::__captured_logs().len(), 1);
+    /// ```
+    pub fn is_logged(&self) -> bool 
+    {
+        self.details.logged.0.load(Ordering::Relaxed)
+    }
+
+    /// Marks the error as logged, so the logging helpers skip it
+    pub fn mark_logged(&self) 
+    {
+        self.details.logged.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Logs the error at its level, unless it was logged already
+    ///
+    /// Goes through the same policies as `debug_error_with_log!`. The target is the
+    /// module the error was created in, `debug_error` if it wasn't created by a macro.
+    pub(crate) fn log_unless_logged(&self) 
+    {
+        if self.is_logged() {
+            return;
+        }
+        crate::logging::log_error(self, self.details.module_path.unwrap_or("debug_error"), format!("Error: {}", self));
+    }
+
+    /// Records the module of the macro call that created the error
+    #[doc(hidden)]
+    pub fn __in_module(mut self, module_path: &'static str) -> Self 
+    {
+        self.details.module_path = Some(module_path);
+        self
+    }
+
+    /// Returns true if the error was created in a file whose path contains the given text
+    ///
+    /// Useful for applying different policies depending on where an error comes from,
+    /// e.g. `err.originated_in("src/db/")`, without matching on the message.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    ///
+    /// mod pool {
+    ///     include!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/pool.rs"));
+    /// }
+    ///
+    /// let pool_err = pool::acquire_connection();
+    /// let local_err = debug_error!("Query failed");
+    ///
+    /// assert!(pool_err.location.file().ends_with("tests/fixtures/pool.rs"));
+    /// assert!(pool_err.originated_in("tests/fixtures/"));
+    /// assert!(pool_err.originated_in("pool.rs"));
+    /// assert!(!local_err.originated_in("tests/fixtures/"));
+    ///
+    /// assert!(local_err.originated_in(file!()));
+    /// assert!(!pool_err.originated_in(file!()));
+    /// ```
+    pub fn originated_in(&self, module_or_file: &str) -> bool 
+    {
+        self.location.file().contains(module_or_file)
+    }
+
+    /// Iterates over this error followed by each of its sources, outermost first
+    ///
+    /// Causes of `with_causes` are visited depth-first: each `DebugError` is followed
+    /// by its source chain, then by each of its causes with their own chains.
+    pub fn chain(&self) -> impl Iterator<Item = &(dyn Error + 'static)> 
+    {
+        let mut pending: Vec<&(dyn Error + 'static)> = vec![self];
+        std::iter::from_fn(move || {
+            let err = pending.pop()?;
+            if let Some(debug_err) = err.downcast_ref::<DebugError>() {
+                pending.extend(debug_err.details.causes.iter().rev().map(|cause| cause as &(dyn Error + 'static)));
+            }
+            pending.extend(err.source());
+            Some(err)
+        })
+    }
+
+    /// Returns the environment captured when the error was created
+    ///
+    /// See `set_captured_env_vars` for which variables are captured.
+    #[cfg(feature = "env-snapshot")]
+    pub fn env(&self) -> &[(String, String)] 
+    {
+        &self.details.env
+    }
+
+    /// Returns the log records that were recorded by `BreadcrumbLogger` before the error
+    #[cfg(feature = "breadcrumbs")]
+    pub fn breadcrumbs(&self) -> &[String] 
+    {
+        &self.details.breadcrumbs
+    }
+
+    /// Returns the message and the rendered sources, e.g. to log them at different levels
+    ///
+    /// Each source is flattened to its `Display` output, so the tail is lossy: sources
+    /// can't be downcast anymore. Use `chain()` if you need the error values. The tail
+    /// follows the source chain only, causes of `with_causes` aren't part of it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    ///
+    /// let io_err = std::io::Error::other("broken pipe");
+    /// let upload = debug_error!("Upload failed").with_source(io_err);
+    /// let err = debug_error!("Sync failed").with_source(upload.clone());
+    ///
+    /// let (head, tail) = err.split_chain();
+    /// assert_eq!(head, "Sync failed");
+    /// assert_eq!(tail, vec![upload.to_string(), "broken pipe".to_string()]);
+    /// ```
+    pub fn split_chain(&self) -> (String, Vec<String>) 
+    {
+        let mut tail = Vec::new();
+        let mut source = Error::source(self);
+        while let Some(err) = source {
+            tail.push(err.to_string());
+            source = err.source();
+        }
+        (self.message.to_string(), tail)
+    }
+
+    /// Renders the error and its sources as an indented tree
+    ///
+    /// Each node shows the message and, for `DebugError` sources, the location. Other
+    /// error types are rendered with their `Display` impl. The children of a node are
+    /// its source followed by its causes of `with_causes`, drawn as siblings.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{debug_error, DebugError};
+    ///
+    /// let io_err = std::io::Error::other("connection reset");
+    /// let query = debug_error!("Query failed").with_source(io_err);
+    /// let err = debug_error!("Loading dashboard failed").with_source(query.clone());
+    ///
+    /// let expected = format!(
+    ///     "Loading dashboard failed at {}\n\
+    ///      └─ Query failed at {}\n\
+    ///      \x20  └─ connection reset",
+    ///     err.location, query.location,
+    /// );
+    /// assert_eq!(err.render_tree(), expected);
+    ///
+    /// // A single error is just the root line
+    /// let single = debug_error!("Alone");
+    /// assert_eq!(single.render_tree(), format!("Alone at {}", single.location));
+    /// ```
+    ///
+    /// Causes are siblings, each with its own subtree:
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    ///
+    /// let eu = debug_error!("eu-west failed").with_source(std::io::Error::other("timed out"));
+    /// let us = debug_error!("us-east failed");
+    /// let err = debug_error!("All replicas failed").with_causes(vec![eu.clone(), us.clone()]);
+    ///
+    /// let expected = format!(
+    ///     "All replicas failed at {}\n\
+    ///      ├─ eu-west failed at {}\n\
+    ///      │  └─ timed out\n\
+    ///      └─ us-east failed at {}",
+    ///     err.location, eu.location, us.location,
+    /// );
+    /// assert_eq!(err.render_tree(), expected);
+    /// ```
+    pub fn render_tree(&self) -> String 
+    {
+        let mut tree = chain_label(self);
+        push_subtree(&mut tree, self, "");
+        tree
+    }
+
+    /// Collapses the error and its sources into a single line, outermost first
+    ///
+    /// Meant for log sinks that only take one string. Like `render_tree`, `DebugError`
+    /// levels show their location and other error types their `Display` impl. Causes of
+    /// `with_causes` follow as a group in brackets, `caused by multiple: [a; b]`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    ///
+    /// let io_err = std::io::Error::other("connection reset");
+    /// let query = debug_error!("Query failed").with_source(io_err);
+    /// let err = debug_error!("Loading dashboard failed").with_source(query.clone());
+    ///
+    /// assert_eq!(
+    ///     err.flatten(),
+    ///     format!(
+    ///         "Loading dashboard failed at {} caused by: Query failed at {} caused by: connection reset",
+    ///         err.location, query.location,
+    ///     )
+    /// );
+    ///
+    /// let eu = debug_error!("eu-west failed");
+    /// let us = debug_error!("us-east failed");
+    /// let err = debug_error!("All replicas failed").with_causes(vec![eu.clone(), us.clone()]);
+    /// assert_eq!(
+    ///     err.flatten(),
+    ///     format!(
+    ///         "All replicas failed at {} caused by multiple: [eu-west failed at {}; us-east failed at {}]",
+    ///         err.location, eu.location, us.location,
+    ///     )
+    /// );
+    /// ```
+    pub fn flatten(&self) -> String 
+    {
+        flatten_from(self)
+    }
+
+    /// Returns the resource usage of the process when the error was created
+    ///
+    /// `None` on platforms other than Linux, or if `/proc` couldn't be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    ///
+    /// let err = debug_error!("Too many open files?");
+    /// # #[cfg(target_os = "linux")]
+    /// # {
+    /// let resources = err.resources().unwrap();
+    /// // At least stdin, stdout and stderr are open
+    /// assert!(resources.open_fds >= 3 && resources.open_fds < 100_000);
+    /// assert!(resources.rss_bytes > 0);
+    /// assert!(format!("{:#}", err).contains("open fds"));
+    /// # }
+    /// ```
+    #[cfg(feature = "resource-snapshot")]
+    pub fn resources(&self) -> Option<&crate::resources::ResourceSnapshot> 
+    {
+        self.details.resources.as_ref()
+    }
+
+    /// Returns the wall clock time the error was created at
+    #[cfg(feature = "timestamp")]
+    pub fn timestamp(&self) -> Option<std::time::SystemTime> 
+    {
+        self.details.timestamp
+    }
+
+    /// Returns how long ago the error was created
+    ///
+    /// Handy for dropping stale errors from queues. If the clock went backwards since
+    /// the error was created, the age is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    /// use std::time::Duration;
+    ///
+    /// let err = debug_error!("Webhook delivery failed");
+    /// std::thread::sleep(Duration::from_millis(20));
+    ///
+    /// let age = err.age().unwrap();
+    /// assert!(age >= Duration::from_millis(20));
+    /// let stale = age > Duration::from_secs(60);
+    /// assert!(!stale);
+    /// ```
+    #[cfg(feature = "timestamp")]
+    pub fn age(&self) -> Option<std::time::Duration> 
+    {
+        let timestamp = self.details.timestamp?;
+        Some(std::time::SystemTime::now().duration_since(timestamp).unwrap_or_default())
+    }
+
+    /// Returns the backtrace captured when the error was created
+    ///
+    /// `None` unless `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` enables capturing.
+    #[cfg(feature = "backtrace")]
+    pub fn backtrace(&self) -> Option<&std::backtrace::Backtrace> 
+    {
+        self.details.backtrace.as_deref()
+    }
+
+    /// Returns the frames of the backtrace, starting at the function that created the error
+    ///
+    /// Meant for error trackers that ingest structured stack frames instead of the raw
+    /// `Backtrace` rendering. Empty if no backtrace was captured.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "backtrace")]
+    /// # {
+    /// use debug_error::{debug_error, DebugError};
+    ///
+    /// // Enable capturing, like `RUST_LIB_BACKTRACE=1` in the environment
+    /// debug_error::__set_env_var("RUST_LIB_BACKTRACE", "1");
+    ///
+    /// fn open_database() -> DebugError {
+    ///     debug_error!("Database locked")
+    /// }
+    ///
+    /// let err = open_database();
+    /// let frames = err.backtrace_frames();
+    ///
+    /// // File and line are only known with debug info
+    /// assert!(frames[0].function.ends_with("open_database"));
+    /// assert!(frames.iter().any(|frame| frame.function.ends_with("main")));
+    /// # #[cfg(feature = "serde")]
+    /// assert!(err.to_json().contains(r#""backtrace":[{"function":"#));
+    /// # }
+    /// ```
+    ///
+    /// Without capturing enabled there are no frames:
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "backtrace")]
+    /// # {
+    /// use debug_error::debug_error;
+    ///
+    /// debug_error::__set_env_var("RUST_LIB_BACKTRACE", "0");
+    ///
+    /// let err = debug_error!("Database locked");
+    /// assert!(err.backtrace().is_none());
+    /// assert!(err.backtrace_frames().is_empty());
+    /// # }
+    /// ```
+    #[cfg(feature = "backtrace")]
+    pub fn backtrace_frames(&self) -> Vec<crate::backtrace::Frame> 
+    {
+        self.backtrace().map(crate::backtrace::frames).unwrap_or_default()
+    }
+
+    /// Returns the `error_context` entries that were active when the error was created
+    pub fn context(&self) -> &[String] 
+    {
+        &self.details.context
     }
 }
 
+impl DebugError 
+{
+    /// Returns the message as rendered, with the `[KIND]` prefix of the `kind-prefix` feature
+    pub(crate) fn display_message(&self) -> std::borrow::Cow<'_, str> 
+    {
+        #[cfg(feature = "kind-prefix")]
+        if let Some(kind) = self.details.kind {
+            return format!("[{}] {}", kind.as_tag(), self.message).into();
+        }
+        Cow::Borrowed(&self.message)
+    }
+
+    /// Writes the `Display` form without any padding, cutting the message to `precision`
+    fn fmt_plain(&self, f: &mut dyn std::fmt::Write, alternate: bool, precision: Option<usize>) -> std::fmt::Result 
+    {
+        match precision {
+            Some(precision) => write!(f, "{:.*}", precision, self.display_message())?,
+            None => write!(f, "{}", self.display_message())?,
+        }
+        if let Some(os_code) = self.details.os_code {
+            write!(f, " (os error {})", os_code)?;
+        }
+        if let Some((current, max)) = self.details.attempt {
+            write!(f, " (attempt {}/{})", current, max)?;
+        }
+        write!(f, "{}{}", crate::config::location_separator(), self.location)?;
+        if !self.details.context.is_empty() {
+            write!(f, " (context: {})", self.details.context.join(" > "))?;
+        }
+        // The alternate form `{:#}` adds the details on their own lines
+        if alternate {
+            self.fmt_details(f)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the detail lines of the alternate form `{:#}`
+    fn fmt_details(&self, f: &mut dyn std::fmt::Write) -> std::fmt::Result 
+    {
+        if !self.details.causes.is_empty() {
+            write!(f, "\n  caused by multiple:")?;
+            for cause in &self.details.causes {
+                write!(f, "\n    - {}", cause)?;
+            }
+        }
+        if let Some(doc_url) = self.doc_url() {
+            write!(f, "\n  see: {}", doc_url)?;
+        }
+        if let Some((method, path)) = self.http_context() {
+            write!(f, "\n  http: {} {}", method, path)?;
+            if let Some(status) = self.details.http_status {
+                write!(f, " (status {})", status)?;
+            }
+        }
+        if !self.details.suggestions.is_empty() {
+            write!(f, "\n  suggestions:")?;
+            for (i, suggestion) in self.details.suggestions.iter().enumerate() {
+                write!(f, "\n    {}. {}", i + 1, suggestion)?;
+            }
+        }
+        if !self.details.kv.is_empty() {
+            let kv: Vec<String> = self.details.kv.iter().map(|(key, value)| format!("{}={}", key, value)).collect();
+            write!(f, "\n  fields: {}", kv.join(", "))?;
+        }
+        #[cfg(feature = "env-snapshot")]
+        if !self.details.env.is_empty() {
+            let env: Vec<String> = self.details.env.iter().map(|(key, value)| format!("{}={}", key, value)).collect();
+            write!(f, "\n  env: {}", env.join(", "))?;
+        }
+        #[cfg(feature = "resource-snapshot")]
+        if let Some(resources) = &self.details.resources {
+            write!(f, "\n  resources: {}", resources)?;
+        }
+        #[cfg(feature = "breadcrumbs")]
+        if !self.details.breadcrumbs.is_empty() {
+            write!(f, "\n  breadcrumbs:")?;
+            for breadcrumb in &self.details.breadcrumbs {
+                write!(f, "\n    - {}", breadcrumb)?;
+            }
+        }
+        #[cfg(feature = "build-info")]
+        write!(f, "\n  build: {}", crate::build_info::build_info())?;
+        // Each source is indented two spaces deeper than the error it caused
+        let mut source = self.details.source.as_deref().map(|source| source as &(dyn Error + 'static));
+        let mut depth = 1;
+        while let Some(err) = source {
+            write!(f, "\n{}caused by: {}", "  ".repeat(depth), err)?;
+            source = err.source();
+            depth += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Renders "message at location", plus the context if any
+///
+/// Width and alignment apply to the complete rendering, so `{:40}` pads errors into
+/// aligned columns. Precision only cuts the message, `{:.20}` keeps its first 20
+/// characters, so the location is always rendered in full.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::DebugError;
+///
+/// let err = DebugError::from_parts("Timeout".to_string(), "src/db.rs".to_string(), 42, 9);
+///
+/// assert_eq!(format!("{}", err), "Timeout at src/db.rs:42:9");
+/// assert_eq!(format!("[{:30}]", err), "[Timeout at src/db.rs:42:9     ]");
+/// assert_eq!(format!("[{:>30}]", err), "[     Timeout at src/db.rs:42:9]");
+/// assert_eq!(format!("{:.4}", err), "Time at src/db.rs:42:9");
+/// assert_eq!(format!("[{:26.4}]", err), "[Time at src/db.rs:42:9    ]");
+/// assert_eq!(format!("[{:-^26.4}]", err), "[--Time at src/db.rs:42:9--]");
+/// ```
+///
+/// The alternate form `{:#}` adds the details on their own lines, followed by the
+/// sources. Each source is indented two spaces deeper than the error it caused, with
+/// its location on the same line:
+///
+/// ```rust
+/// use debug_error::debug_error;
+///
+/// let io_err = std::io::Error::other("connection reset");
+/// let query = debug_error!("Query failed").with_source(io_err);
+/// let load = debug_error!("Loading orders failed").with_source(query.clone());
+/// let err = debug_error!("Rendering dashboard failed").with_source(load.clone());
+///
+/// let rendered = format!("{:#}", err);
+/// let chain: Vec<&str> = rendered.lines().filter(|line| line.trim_start().starts_with("caused by: ")).collect();
+/// assert!(rendered.starts_with(&format!("Rendering dashboard failed at {}", err.location)));
+/// assert_eq!(chain, [
+///     format!("  caused by: Loading orders failed at {}", load.location),
+///     format!("    caused by: Query failed at {}", query.location),
+///     "      caused by: connection reset".to_string(),
+/// ]);
+/// ```
 impl std::fmt::Display for DebugError 
 {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result 
     {
-        write!(f, "{} at {}", self.message, self.location)
+        let alternate = f.alternate();
+        let precision = f.precision();
+        let Some(width) = f.width() else {
+            return self.fmt_plain(f, alternate, precision);
+        };
+        // Padding needs the length up front, so render into a buffer first. `f.pad`
+        // can't be used, it would apply the precision to the whole rendering again.
+        let mut rendered = String::new();
+        self.fmt_plain(&mut rendered, alternate, precision)?;
+        let padding = width.saturating_sub(rendered.chars().count());
+        let (before, after) = match f.align() {
+            Some(std::fmt::Alignment::Right) => (padding, 0),
+            Some(std::fmt::Alignment::Center) => (padding / 2, padding - padding / 2),
+            _ => (0, padding),
+        };
+        let fill = f.fill();
+        for _ in 0..before {
+            write!(f, "{}", fill)?;
+        }
+        f.write_str(&rendered)?;
+        for _ in 0..after {
+            write!(f, "{}", fill)?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats the message of an error created by the macros
+///
+/// With the `robust-format` feature, a panic in a `Display` impl of a format argument
+/// is caught and replaced by a fallback message, so creating an error never panics.
+/// Catching costs a little on every error creation, which is why it is opt-in. The
+/// panic hook still runs, so the panic message is printed as usual.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(feature = "robust-format")]
+/// # {
+/// use debug_error::debug_error;
+///
+/// struct Broken;
+///
+/// impl std::fmt::Display for Broken {
+///     fn fmt(&self, _: &mut std::fmt::Formatter) -> std::fmt::Result {
+///         panic!("Display is broken")
+///     }
+/// }
+///
+/// let err = debug_error!("Failed with {}", Broken);
+/// assert_eq!(err.message, "<message formatting panicked>");
+/// # }
+/// ```
+#[doc(hidden)]
+pub fn __format_message(format: impl FnOnce() -> String) -> String 
+{
+    #[cfg(feature = "robust-format")]
+    {
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(format))
+            .unwrap_or_else(|_| "<message formatting panicked>".to_string())
+    }
+    #[cfg(not(feature = "robust-format"))]
+    {
+        format()
+    }
+}
+
+/// Turns the arguments of the macros into the message
+///
+/// Literal messages without placeholders are known at compile time and borrowed,
+/// everything else goes through `__format_message`.
+#[doc(hidden)]
+pub fn __message(args: std::fmt::Arguments) -> Cow<'static, str> 
+{
+    match args.as_str() {
+        Some(literal) => Cow::Borrowed(literal),
+        None => Cow::Owned(__format_message(|| std::fmt::format(args))),
+    }
+}
+
+/// Converts boxed errors, so `?` works on functions returning `Box<dyn Error + Send + Sync>`
+///
+/// The message is the boxed error's `Display` output and the box is kept as the
+/// source. The location is where the conversion happens, i.e. the `?`.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::DebugError;
+/// use std::sync::atomic::{AtomicU32, Ordering};
+///
+/// static QUESTION_MARK_LINE: AtomicU32 = AtomicU32::new(0);
+///
+/// fn legacy_parse(input: &str) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
+///     Ok(input.parse::<u32>()?)
+/// }
+///
+/// fn load() -> Result<u32, DebugError> {
+///     QUESTION_MARK_LINE.store(line!(), Ordering::Relaxed); let value = legacy_parse("nan")?;
+///     Ok(value)
+/// }
+///
+/// let err = load().unwrap_err();
+/// assert_eq!(err.message, "invalid digit found in string");
+/// assert_eq!(err.location.line(), QUESTION_MARK_LINE.load(Ordering::Relaxed));
+/// assert!(err.chain().nth(1).unwrap().downcast_ref::<std::num::ParseIntError>().is_some());
+/// ```
+impl From<Box<dyn Error + Send + Sync + 'static>> for DebugError 
+{
+    #[track_caller]
+    fn from(err: Box<dyn Error + Send + Sync + 'static>) -> Self 
+    {
+        let mut debug_err = DebugError::new(err.to_string(), std::panic::Location::caller());
+        debug_err.set_source(Arc::from(err));
+        debug_err
+    }
+}
+
+/// Converts io errors, keeping the raw OS error code
+///
+/// The io error is kept as the source and the location is where the conversion
+/// happens, i.e. the `?`.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::DebugError;
+///
+/// // 13 is EACCES (permission denied) on Linux and macOS
+/// let io_err = std::io::Error::from_raw_os_error(13);
+/// let err = DebugError::from(io_err);
+///
+/// assert_eq!(err.os_code(), Some(13));
+/// assert!(err.to_string().contains(" (os error 13) at "));
+/// assert!(err.chain().nth(1).unwrap().downcast_ref::<std::io::Error>().is_some());
+///
+/// let custom = DebugError::from(std::io::Error::other("disk on fire"));
+/// assert_eq!(custom.os_code(), None);
+/// assert_eq!(custom.message, "disk on fire");
+/// ```
+impl From<std::io::Error> for DebugError 
+{
+    #[track_caller]
+    fn from(err: std::io::Error) -> Self 
+    {
+        let os_code = err.raw_os_error();
+        let mut message = err.to_string();
+        // The code is rendered by `Display` itself, don't repeat it in the message
+        if let Some(code) = os_code {
+            let suffix = format!(" (os error {})", code);
+            if let Some(stripped) = message.strip_suffix(&suffix) {
+                message = stripped.to_string();
+            }
+        }
+        let mut debug_err = DebugError::new(message, std::panic::Location::caller());
+        debug_err.details.os_code = os_code;
+        debug_err.with_source(err)
     }
 }
 
-impl ::std::error::Error for DebugError {}
+impl DebugError 
+{
+    /// Wraps the error into an `io::Error`, for APIs with io-typed signatures
+    ///
+    /// The io error kind is derived from the kind, see `Kind::io_error_kind`, and is
+    /// `Other` without a kind. The `DebugError` stays available as the inner error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{debug_error, DebugError, Kind};
+    /// use std::io;
+    ///
+    /// fn read_block(index: u64) -> io::Result<Vec<u8>> {
+    ///     Err(debug_error!("Block {} not in cache", index).with_kind(Kind::NotFound).into_io_error())
+    /// }
+    ///
+    /// let io_err = read_block(7).unwrap_err();
+    /// assert_eq!(io_err.kind(), io::ErrorKind::NotFound);
+    ///
+    /// let inner = io_err.get_ref().unwrap().downcast_ref::<DebugError>().unwrap();
+    /// assert_eq!(inner.message, "Block 7 not in cache");
+    ///
+    /// assert_eq!(debug_error!("Checksum mismatch").into_io_error().kind(), io::ErrorKind::Other);
+    /// ```
+    pub fn into_io_error(self) -> std::io::Error 
+    {
+        let kind = self.details.kind.map_or(std::io::ErrorKind::Other, |kind| kind.io_error_kind());
+        std::io::Error::new(kind, self)
+    }
+}
+
+/// Renders one level of a chain, with the location for `DebugError` levels
+fn chain_label(err: &(dyn Error + 'static)) -> String 
+{
+    match err.downcast_ref::<DebugError>() {
+        Some(debug_err) => format!("{}{}{}", debug_err.message, crate::config::location_separator(), debug_err.location),
+        None => err.to_string(),
+    }
+}
+
+/// Returns the source of an error followed by its causes, if it is a `DebugError`
+fn chain_children<'a>(err: &'a (dyn Error + 'static)) -> Vec<&'a (dyn Error + 'static)> 
+{
+    let mut children: Vec<&(dyn Error + 'static)> = err.source().into_iter().collect();
+    if let Some(debug_err) = err.downcast_ref::<DebugError>() {
+        children.extend(debug_err.details.causes.iter().map(|cause| cause as &(dyn Error + 'static)));
+    }
+    children
+}
+
+/// Appends the children of `err` to a tree of `render_tree`, below lines starting with `prefix`
+fn push_subtree(tree: &mut String, err: &(dyn Error + 'static), prefix: &str) 
+{
+    let children = chain_children(err);
+    for (i, child) in children.iter().enumerate() {
+        let last = i + 1 == children.len();
+        tree.push('\n');
+        tree.push_str(prefix);
+        tree.push_str(if last { "└─ " } else { "├─ " });
+        tree.push_str(&chain_label(*child));
+        push_subtree(tree, *child, &format!("{}{}", prefix, if last { "   " } else { "│  " }));
+    }
+}
+
+/// Renders an error for `flatten`, its source chain inline and its causes as a group
+fn flatten_from(err: &(dyn Error + 'static)) -> String 
+{
+    let mut flat = chain_label(err);
+    if let Some(source) = err.source() {
+        flat.push_str(" caused by: ");
+        flat.push_str(&flatten_from(source));
+    }
+    let causes = err.downcast_ref::<DebugError>().map_or(&[][..], |debug_err| &debug_err.details.causes);
+    if !causes.is_empty() {
+        let causes: Vec<String> = causes.iter().map(|cause| flatten_from(cause)).collect();
+        flat.push_str(&format!(" caused by multiple: [{}]", causes.join("; ")));
+    }
+    flat
+}
+
+/// Keeps at most `keep` errors of the chain starting at `source`, dropping the oldest
+///
+/// `DebugError` links are copied with a shorter source, which only happens when the
+/// chain is too long. Other error types can't be cut and are kept whole.
+fn truncate_chain(source: Arc<dyn Error + Send + Sync + 'static>, keep: usize) -> Option<Arc<dyn Error + Send + Sync + 'static>> 
+{
+    if keep == 0 {
+        return None;
+    }
+    let mut len = 0;
+    let mut link = Some(&*source as &(dyn Error + 'static));
+    while let Some(err) = link {
+        len += 1;
+        link = err.source();
+    }
+    if len <= keep {
+        return Some(source);
+    }
+    let Some(debug_err) = (&*source as &(dyn Error + 'static)).downcast_ref::<DebugError>() else {
+        return Some(source);
+    };
+    let mut copy = debug_err.clone();
+    copy.details.source = copy.details.source.take().and_then(|inner| truncate_chain(inner, keep - 1));
+    Some(Arc::new(copy))
+}
+
+impl ::std::error::Error for DebugError 
+{
+    fn source(&self) -> Option<&(dyn Error + 'static)> 
+    {
+        self.details.source.as_deref().map(|source| source as &(dyn Error + 'static))
+    }
+}
 
 /// Creates a DebugError and automatically logs it
 ///
@@ -98,15 +1912,46 @@ impl ::std::error::Error for DebugError {}
 ///     Err(debug_error_with_log!("Database connection timeout after 30s"))
 /// }
 /// ```
+///
+/// Fields before the format string, in the syntax of `tracing`, are added with
+/// `DebugError::with_kv` and logged as structured fields of the record:
+///
+/// ```rust
+/// use debug_error::debug_error_with_log;
+/// # debug_error::__capture_logs();
+///
+/// let user_id = 5;
+/// let err = debug_error_with_log!(user_id = user_id, request = "GET /", "Request failed");
+///
+/// assert_eq!(err.message, "Request failed");
+/// assert_eq!(err.kv(), [("user_id", "5".to_string()), ("request", "GET /".to_string())]);
+/// let record = &debug_error::__captured_logs()[0];
+/// assert_eq!((record.field("user_id"), record.field("request")), (Some("5"), Some("GET /")));
+/// ```
 #[macro_export]
 macro_rules! debug_error_with_log
 {
+    (code = $code:expr, $($arg:tt)*) => {{
+        let err = $crate::debug_error!(code = $code, $($arg)*);
+        $crate::__log_created(err, module_path!())
+    }};
+    (category = $category:expr, $($arg:tt)*) => {{
+        let err = $crate::debug_error!(category = $category, $($arg)*);
+        $crate::__log_created(err, module_path!())
+    }};
+    (user_error, $($arg:tt)*) => {{
+        let err = $crate::debug_error!(user_error, $($arg)*);
+        $crate::__log_created(err, module_path!())
+    }};
+    ($key:ident = $value:expr, $($rest:tt)+) => {{
+        let err = $crate::debug_error!($key = $value, $($rest)+);
+        $crate::__log_created(err, module_path!())
+    }};
     ($($arg:tt)*) => {{
-        let message = format!($($arg)*); // Format the message
-        let err = DebugError::new(message, std::panic::Location::caller());
+        let message = $crate::__message(format_args!($($arg)*)); // Format the message
+        let err = $crate::DebugError::new(message, std::panic::Location::caller()).__in_module(module_path!());
         // Log the error with the location
-        ::log::error!("Error: {} at {}:{}:{}", err.message, err.location.file(), err.location.line(), err.location.column());
-        err
+        $crate::__log_created(err, module_path!())
     }};
 }
 
@@ -121,6 +1966,11 @@ macro_rules! debug_error_with_log
 /// - When using custom error handling middleware
 /// - When you have proper error handling infrastructure set up
 ///
+/// Start with `code = ...` to set a code declared with `declare_error_codes!`:
+/// `debug_error!(code = E_DB_TIMEOUT, "Query timed out")`.
+/// Start with `user_error,` to mark the error with `DebugError::user_error`. Fields like
+/// `debug_error!(user_id = 5, "Request failed")` are added with `DebugError::with_kv`.
+///
 /// # Examples
 ///
 /// ```rust
@@ -138,10 +1988,282 @@ macro_rules! debug_error_with_log
 /// ```
 #[macro_export]
 macro_rules! debug_error
+{
+    (code = $code:expr, $($arg:tt)*) => {{
+        $crate::debug_error!($($arg)*).with_code($crate::IntoErrorCode::into_error_code($code))
+    }};
+    (category = $category:expr, $($arg:tt)*) => {{
+        $crate::debug_error!($($arg)*).with_category($category)
+    }};
+    (user_error, $($arg:tt)*) => {{
+        $crate::debug_error!($($arg)*).user_error()
+    }};
+    // Fields are collected first, so they are added in the order they were written
+    ($key:ident = $value:expr, $($rest:tt)+) => {{
+        $crate::debug_error!(@fields [($key, $value)] $($rest)+)
+    }};
+    (@fields [$(($field:ident, $field_value:expr))*] $key:ident = $value:expr, $($rest:tt)+) => {{
+        $crate::debug_error!(@fields [$(($field, $field_value))* ($key, $value)] $($rest)+)
+    }};
+    (@fields [$(($field:ident, $field_value:expr))*] $($arg:tt)+) => {{
+        $crate::debug_error!($($arg)+)$(.with_kv(stringify!($field), $field_value))*
+    }};
+    ($($arg:tt)*) => {{
+        let message = $crate::__message(format_args!($($arg)*)); // Format the message
+        let err = $crate::DebugError::new(message, std::panic::Location::caller()).__in_module(module_path!());
+        err
+    }};
+}
+
+/// Creates and logs a DebugError in debug builds only, for development diagnostics
+///
+/// In debug builds it works like `debug_error_with_log!` and returns
+/// `Some(DebugError)`. In release builds it returns `None` without formatting or
+/// logging anything. The profile is the one of the crate calling the macro, as it
+/// expands there (`cfg(debug_assertions)`).
+///
+/// The type is `Option<DebugError>` in both profiles, so code using the macro compiles
+/// in both. Don't let control flow depend on `Some`: handle the value with
+/// `if let Some(err) = ...`, and never `unwrap` it, which would panic in release.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::debug_only_error;
+/// # debug_error::__capture_logs();
+///
+/// let cache_hits = 3;
+/// let diagnostic = debug_only_error!("Unexpectedly few cache hits: {}", cache_hits);
+///
+/// #[cfg(debug_assertions)]
+/// {
+///     assert_eq!(diagnostic.unwrap().message, "Unexpectedly few cache hits: 3");
+///     assert!(debug_error::__captured_logs()[0].message.starts_with("Error: Unexpectedly few cache hits: 3 at "));
+/// }
+/// #[cfg(not(debug_assertions))]
+/// {
+///     assert!(diagnostic.is_none());
+///     assert!(debug_error::__captured_logs().is_empty());
+/// }
+/// ```
+#[macro_export]
+macro_rules! debug_only_error
 {
     ($($arg:tt)*) => {{
-        let message = format!($($arg)*); // Format the message
-        let err = DebugError::new(message, std::panic::Location::caller());
+        #[cfg(debug_assertions)]
+        let err = Some($crate::debug_error_with_log!($($arg)*));
+        #[cfg(not(debug_assertions))]
+        let err = {
+            // Never runs, but keeps the arguments used so release builds don't warn
+            if false {
+                let _ = format_args!($($arg)*);
+            }
+            None::<$crate::DebugError>
+        };
         err
     }};
+}
+
+/// Creates a DebugError that records the version of the calling crate
+///
+/// The version is read with `env!("CARGO_PKG_VERSION")` inside the macro, which
+/// expands in your crate, so it's your version, not the one of `debug_error`. That is
+/// also why it has to be a macro: a function of this crate could only ever see its
+/// own version. In a workspace each crate records its own version, not the one of
+/// the binary. The version is included in the JSON output as `crate_version`.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::debug_error_versioned;
+///
+/// let err = debug_error_versioned!("Migration {} failed", 12);
+///
+/// assert_eq!(err.message, "Migration 12 failed");
+/// assert_eq!(err.crate_version(), Some(env!("CARGO_PKG_VERSION")));
+/// # #[cfg(feature = "serde")]
+/// # assert!(err.to_json().contains(&format!("\"crate_version\":\"{}\"", env!("CARGO_PKG_VERSION"))));
+/// ```
+#[macro_export]
+macro_rules! debug_error_versioned
+{
+    ($($arg:tt)*) => {{
+        $crate::debug_error!($($arg)*).with_crate_version(env!("CARGO_PKG_VERSION"))
+    }};
+}
+
+/// Returns early with a DebugError if the condition is false
+///
+/// The message is built from the source text of the condition, so you get the
+/// failing expression and its location without writing a message yourself.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{check, DebugError};
+///
+/// fn validate_port(port: u32) -> Result<u32, DebugError> {
+///     check!(port > 0 && port <= 65535);
+///     Ok(port)
+/// }
+///
+/// assert!(validate_port(8080).is_ok());
+///
+/// let err = validate_port(0).unwrap_err();
+/// assert_eq!(err.message, "check failed: port > 0 && port <= 65535");
+/// ```
+#[macro_export]
+macro_rules! check
+{
+    ($cond:expr $(,)?) => {{
+        if !$cond {
+            let message = format!("check failed: {}", stringify!($cond));
+            let err = $crate::DebugError::new(message, std::panic::Location::caller()).__in_module(module_path!());
+            return Err(err.into());
+        }
+    }};
+}
+
+/// Returns early with a DebugError carrying an HTTP status
+///
+/// Meant for web handlers returning `Result<_, DebugError>`: the framework integration
+/// (e.g. the `axum` feature) turns the error into a response with that status, while
+/// the location of the `http_bail!` is kept for server-side logging.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{http_bail, DebugError};
+///
+/// fn get_user(id: u32) -> Result<String, DebugError> {
+///     if id == 0 {
+///         http_bail!(400, "User id must not be 0");
+///     }
+///     if id > 100 {
+///         http_bail!(404, "User {} not found", id);
+///     }
+///     Ok(format!("user {}", id))
+/// }
+///
+/// assert_eq!(get_user(7).unwrap(), "user 7");
+///
+/// let err = get_user(101).unwrap_err();
+/// assert_eq!(err.http_status(), Some(404));
+/// assert_eq!(err.message, "User 101 not found");
+/// assert_eq!(err.location.line(), line!() - 10);
+/// ```
+#[macro_export]
+macro_rules! http_bail
+{
+    ($status:expr, $($arg:tt)*) => {{
+        let err = $crate::debug_error!($($arg)*).with_http_status($status);
+        return Err(err.into());
+    }};
+}
+
+/// Runs a block in which `?` works, like an unstable `try` block
+///
+/// The block is wrapped in a closure returning `Result<T, DebugError>` which is invoked
+/// immediately, so you don't need a separate function to use `?`. Note that `return`
+/// inside the block returns from the block, not from the surrounding function.
+///
+/// With a leading context string, any error from the block is wrapped in a new
+/// `DebugError` carrying the context and the location of the block, with the original
+/// error as its source.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{try_block, debug_error, DebugError};
+///
+/// fn parse(input: &str) -> Result<u32, DebugError> {
+///     input.parse().map_err(|e| debug_error!("invalid number '{}': {}", input, e))
+/// }
+///
+/// let sum = try_block!({
+///     let a = parse("1")?;
+///     let b = parse("2")?;
+///     Ok(a + b)
+/// });
+/// assert_eq!(sum.unwrap(), 3);
+///
+/// let err = try_block!("while loading settings", {
+///     let port = parse("eighty")?;
+///     Ok(port)
+/// })
+/// .unwrap_err();
+/// assert_eq!(err.message, "while loading settings");
+/// assert!(err.chain().nth(1).unwrap().to_string().starts_with("invalid number 'eighty'"));
+/// ```
+#[macro_export]
+macro_rules! try_block
+{
+    ($body:block) => {{
+        (|| -> Result<_, $crate::DebugError> { $body })()
+    }};
+    ($context:expr, $body:block) => {{
+        let location = std::panic::Location::caller();
+        (|| -> Result<_, $crate::DebugError> { $body })()
+            .map_err(|err| $crate::DebugError::new($context.to_string(), location).__in_module(module_path!()).with_source(err))
+    }};
+}
+
+/// Creates a DebugError with a formatted message and the given error as its source
+///
+/// The value-producing counterpart of wrapping an error in `map_err`: the source
+/// expression is evaluated, the message is formatted and the caller location is
+/// captured, all in one call. The error is returned, not propagated.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::wrap_err;
+///
+/// let io_err = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "access denied");
+/// let err = wrap_err!(io_err, "Failed to open {}", "/etc/shadow");
+///
+/// assert_eq!(err.message, "Failed to open /etc/shadow");
+/// let source = err.chain().nth(1).unwrap().downcast_ref::<std::io::Error>().unwrap();
+/// assert_eq!(source.kind(), std::io::ErrorKind::PermissionDenied);
+/// ```
+#[macro_export]
+macro_rules! wrap_err
+{
+    ($source:expr, $($arg:tt)*) => {{
+        let source = $source;
+        let message = $crate::__message(format_args!($($arg)*)); // Format the message
+        $crate::DebugError::new(message, std::panic::Location::caller()).__in_module(module_path!()).with_source(source)
+    }};
+}
+
+/// Creates a DebugError together with its rendered message
+///
+/// Useful in HTTP handlers that log the error and also return the text in the
+/// response body, without calling `to_string()` separately.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::debug_error_pair;
+/// use log::error;
+///
+/// fn handle_request(id: u32) -> (u16, String) {
+///     let (err, body) = debug_error_pair!("Order {} not found", id);
+///     error!("{}", err);
+///     (404, body)
+/// }
+///
+/// let (err, body) = debug_error_pair!("Order {} not found", 7);
+/// assert_eq!(body, err.to_string());
+/// assert!(body.starts_with("Order 7 not found at "));
+/// # assert_eq!(handle_request(7).0, 404);
+/// ```
+#[macro_export]
+macro_rules! debug_error_pair
+{
+    ($($arg:tt)*) => {{
+        let err = $crate::debug_error!($($arg)*);
+        let rendered = err.to_string();
+        (err, rendered)
+    }};
 }
\ No newline at end of file
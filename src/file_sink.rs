@@ -0,0 +1,130 @@
+//! Error log file with size based rotation, enabled with the `file-sink` feature
+//!
+//! For standalone tools without a logging framework: once `set_error_log_file` is
+//! called, `debug_error_with_log!` writes its records to the file instead of the `log`
+//! logger.
+
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::DebugError;
+
+/// Number of rotated files kept next to the log file, `.1` being the newest
+const ROTATED_FILES: u32 = 5;
+
+static FILE_SINK: Mutex<Option<FileSink>> = Mutex::new(None);
+
+/// The open log file and how much was written to it
+struct FileSink 
+{
+    path: PathBuf,
+    max_size: u64,
+    file: File,
+    size: u64,
+}
+
+impl FileSink 
+{
+    /// Writes a line, rotating first if it would make the file exceed `max_size`
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> 
+    {
+        if self.size > 0 && self.size + line.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Shifts `.1` to `.2` and so on, moves the log file to `.1` and starts a new one
+    fn rotate(&mut self) -> std::io::Result<()> 
+    {
+        for n in (1..ROTATED_FILES).rev() {
+            let from = rotated_path(&self.path, n);
+            if from.exists() {
+                fs::rename(from, rotated_path(&self.path, n + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        self.file = File::create(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// Returns the path of the `n`th rotated file, e.g. `errors.log.2`
+fn rotated_path(path: &Path, n: u32) -> PathBuf 
+{
+    let mut name = OsString::from(path.as_os_str());
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// Routes the records of `debug_error_with_log!` to a file, rotated at `max_size` bytes
+///
+/// Records are appended as "LEVEL message" lines. Before a line would make the file
+/// larger than `max_size`, the file is renamed to `<path>.1`, older files move on to
+/// `.2` and so on, and the 5 newest rotated files are kept. Errors are written at any
+/// level, the max level of the `log` crate doesn't apply. Writes are serialized with a
+/// mutex, so threads never interleave their lines. Calling it again switches to the
+/// new file.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error_with_log, set_error_log_file};
+///
+/// let dir = std::env::temp_dir().join(format!("debug_error_file_sink_{}", std::process::id()));
+/// std::fs::create_dir_all(&dir).unwrap();
+/// let path = dir.join("errors.log");
+///
+/// set_error_log_file(&path, 200).unwrap();
+/// for job in 0..20 {
+///     let _ = debug_error_with_log!("Job {} failed", job);
+/// }
+///
+/// let current = std::fs::read_to_string(&path).unwrap();
+/// let rotated = std::fs::read_to_string(dir.join("errors.log.1")).unwrap();
+/// assert!(current.starts_with("ERROR Error: Job "));
+/// assert!(current.contains("Job 19 failed"));
+/// assert!(!rotated.contains("Job 19 failed"));
+/// for file in ["errors.log", "errors.log.1", "errors.log.2"] {
+///     assert!(std::fs::metadata(dir.join(file)).unwrap().len() <= 200);
+/// }
+/// # std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[track_caller]
+pub fn set_error_log_file(path: impl AsRef<Path>, max_size: u64) -> Result<(), DebugError> 
+{
+    let location = std::panic::Location::caller();
+    let path = path.as_ref().to_path_buf();
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|err| DebugError::new(format!("Failed to open error log {}", path.display()), location).with_source(err))?;
+    let size = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+    *FILE_SINK.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(FileSink { path, max_size, file, size });
+    Ok(())
+}
+
+/// Returns true if `set_error_log_file` was called
+pub(crate) fn active() -> bool 
+{
+    FILE_SINK.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).is_some()
+}
+
+/// Writes a record to the log file, false if there is none
+pub(crate) fn write(level: ::log::Level, message: &str) -> bool 
+{
+    let mut sink = FILE_SINK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let Some(sink) = sink.as_mut() else {
+        return false;
+    };
+    // There is nowhere left to report a failing error log to
+    let _ = sink.write_line(&format!("{} {}\n", level, message));
+    true
+}
@@ -0,0 +1,74 @@
+//! GitHub Actions workflow commands, so errors show up inline on pull requests
+//!
+//! ```text
+//! ::error file=src/config.rs,line=12::Invalid port
+//! ```
+//!
+//! When the `GITHUB_ACTIONS` environment variable is set, as it is on every GitHub
+//! runner, `debug_error_with_log!` also prints errors in this format to stdout, next
+//! to passing them to the logger.
+
+use std::sync::OnceLock;
+
+use crate::DebugError;
+
+/// Returns true when running on GitHub Actions, checked once per process
+pub(crate) fn github_actions() -> bool 
+{
+    static GITHUB_ACTIONS: OnceLock<bool> = OnceLock::new();
+    *GITHUB_ACTIONS.get_or_init(|| std::env::var_os("GITHUB_ACTIONS").is_some_and(|value| !value.is_empty()))
+}
+
+/// Escapes the message of a workflow command
+fn escape_data(data: &str) -> String 
+{
+    data.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Escapes a property value of a workflow command, which also can't contain `:` or `,`
+fn escape_property(value: &str) -> String 
+{
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+impl DebugError 
+{
+    /// Renders the error as a GitHub Actions annotation
+    ///
+    /// Errors logged at warn level become `::warning`, lower levels `::notice`. Newlines
+    /// and other special characters are escaped, so the annotation always stays on a
+    /// single line.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::DebugError;
+    ///
+    /// let err = DebugError::from_parts(
+    ///     "Snapshot mismatch:\nexpected 100%\ngot 90%".to_string(),
+    ///     "tests/snapshots.rs".to_string(),
+    ///     42,
+    ///     9,
+    /// );
+    ///
+    /// assert_eq!(
+    ///     err.render_gha(),
+    ///     "::error file=tests/snapshots.rs,line=42::Snapshot mismatch:%0Aexpected 100%25%0Agot 90%25"
+    /// );
+    /// ```
+    pub fn render_gha(&self) -> String 
+    {
+        let command = match self.level() {
+            ::log::Level::Error => "error",
+            ::log::Level::Warn => "warning",
+            _ => "notice",
+        };
+        format!(
+            "::{} file={},line={}::{}",
+            command,
+            escape_property(self.location.file()),
+            self.location.line(),
+            escape_data(&self.message)
+        )
+    }
+}
@@ -0,0 +1,93 @@
+//! systemd journal output, enabled with the `journald` feature on Linux
+//!
+//! Entries are sent with the native journal protocol to the journald socket, so the
+//! location and context end up as structured fields that `journalctl` can filter on,
+//! e.g. `journalctl CODE_FILE=src/db.rs`.
+
+use std::io;
+use std::os::unix::net::UnixDatagram;
+
+use crate::DebugError;
+
+const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// Maps a log level to a syslog priority as used by the journal
+fn priority(level: ::log::Level) -> u8 
+{
+    match level {
+        ::log::Level::Error => 3,
+        ::log::Level::Warn => 4,
+        ::log::Level::Info => 6,
+        ::log::Level::Debug | ::log::Level::Trace => 7,
+    }
+}
+
+/// Appends one field in the native journal protocol format
+fn encode_field(buf: &mut Vec<u8>, name: &str, value: &str) 
+{
+    buf.extend_from_slice(name.as_bytes());
+    if value.contains('\n') {
+        // Values containing newlines are sent with an explicit little endian length
+        buf.push(b'\n');
+        buf.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        buf.push(b'=');
+    }
+    buf.extend_from_slice(value.as_bytes());
+    buf.push(b'\n');
+}
+
+impl DebugError 
+{
+    /// Returns the journal fields written by `log_journald`
+    ///
+    /// Context entries become repeated `ERROR_CONTEXT` fields and the key-value pairs of
+    /// `with_kv` repeated `ERROR_FIELD` fields, as `key=value`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{debug_error, error_context};
+    /// use log::Level;
+    ///
+    /// let _g = error_context("rotating keys");
+    /// let err = debug_error!("Key store locked").with_level(Level::Warn).with_kv("key_id", 42);
+    /// let fields = err.journal_fields();
+    ///
+    /// assert!(fields.contains(&("PRIORITY", "4".to_string())));
+    /// assert!(fields.contains(&("MESSAGE", err.to_string())));
+    /// assert!(fields.contains(&("CODE_FILE", err.location.file().to_string())));
+    /// assert!(fields.contains(&("CODE_LINE", err.location.line().to_string())));
+    /// assert!(fields.contains(&("ERROR_CONTEXT", "rotating keys".to_string())));
+    /// assert!(fields.contains(&("ERROR_FIELD", "key_id=42".to_string())));
+    /// ```
+    pub fn journal_fields(&self) -> Vec<(&'static str, String)> 
+    {
+        let mut fields = vec![
+            ("PRIORITY", priority(self.level()).to_string()),
+            ("MESSAGE", self.to_string()),
+            ("CODE_FILE", self.location.file().to_string()),
+            ("CODE_LINE", self.location.line().to_string()),
+            ("CODE_COLUMN", self.location.column().to_string()),
+        ];
+        // The journal allows repeating a field, which keeps the context order intact
+        fields.extend(self.context().iter().map(|context| ("ERROR_CONTEXT", context.clone())));
+        fields.extend(self.kv().iter().map(|(key, value)| ("ERROR_FIELD", format!("{}={}", key, value))));
+        fields
+    }
+
+    /// Writes the error to the systemd journal
+    ///
+    /// The priority is derived from the error's level. Fails if journald isn't running
+    /// or the entry is larger than the maximum datagram size of the socket.
+    pub fn log_journald(&self) -> io::Result<()> 
+    {
+        let mut buf = Vec::new();
+        for (name, value) in self.journal_fields() {
+            encode_field(&mut buf, name, &value);
+        }
+        let socket = UnixDatagram::unbound()?;
+        socket.send_to(&buf, JOURNAL_SOCKET)?;
+        Ok(())
+    }
+}
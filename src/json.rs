@@ -0,0 +1,254 @@
+//! JSON output, enabled with the `serde` feature
+//!
+//! `DebugError` implements `Serialize` as a flat object. Optional parts are left out
+//! when they are empty, so the common case stays compact:
+//!
+//! ```json
+//! {"message":"Timeout","file":"src/db.rs","line":42,"column":9,"seq":0,"code":"DB-001"}
+//! ```
+
+use ::serde::ser::{Serialize, SerializeMap, Serializer};
+
+use crate::DebugError;
+
+impl Serialize for DebugError 
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> 
+    {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("message", &self.message)?;
+        map.serialize_entry("file", self.location.file())?;
+        map.serialize_entry("line", &self.location.line())?;
+        map.serialize_entry("column", &self.location.column())?;
+        map.serialize_entry("seq", &self.seq())?;
+        if let Some(code) = self.code() {
+            map.serialize_entry("code", code)?;
+        }
+        if let Some(doc_url) = self.doc_url() {
+            map.serialize_entry("doc_url", &doc_url)?;
+        }
+        if let Some((attempt, max_attempts)) = self.attempt() {
+            map.serialize_entry("attempt", &attempt)?;
+            map.serialize_entry("max_attempts", &max_attempts)?;
+        }
+        if let Some((method, path)) = self.http_context() {
+            let http = ::serde_json::json!({
+                "method": method,
+                "path": path,
+                "status": self.http_status(),
+            });
+            map.serialize_entry("http", &http)?;
+        }
+        if let Some(category) = self.category() {
+            map.serialize_entry("category", category)?;
+        }
+        if let Some(crate_version) = self.crate_version() {
+            map.serialize_entry("crate_version", crate_version)?;
+        }
+        if !self.context().is_empty() {
+            map.serialize_entry("context", self.context())?;
+        }
+        if !self.kv().is_empty() {
+            let kv: ::serde_json::Map<String, ::serde_json::Value> = self
+                .kv()
+                .iter()
+                .map(|(key, value)| (key.to_string(), ::serde_json::Value::from(value.as_str())))
+                .collect();
+            map.serialize_entry("fields", &kv)?;
+        }
+        if !self.suggestions().is_empty() {
+            map.serialize_entry("suggestions", self.suggestions())?;
+        }
+        let sources: Vec<String> = self.chain().skip(1).map(|source| source.to_string()).collect();
+        if !sources.is_empty() {
+            map.serialize_entry("sources", &sources)?;
+        }
+        #[cfg(feature = "env-snapshot")]
+        if !self.env().is_empty() {
+            let env: ::serde_json::Map<String, ::serde_json::Value> = self
+                .env()
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone().into()))
+                .collect();
+            map.serialize_entry("env", &env)?;
+        }
+        #[cfg(feature = "resource-snapshot")]
+        if let Some(resources) = self.resources() {
+            let resources = ::serde_json::json!({
+                "open_fds": resources.open_fds,
+                "rss_bytes": resources.rss_bytes,
+            });
+            map.serialize_entry("resources", &resources)?;
+        }
+        #[cfg(feature = "breadcrumbs")]
+        if !self.breadcrumbs().is_empty() {
+            map.serialize_entry("breadcrumbs", self.breadcrumbs())?;
+        }
+        #[cfg(feature = "backtrace")]
+        {
+            let frames = self.backtrace_frames();
+            if !frames.is_empty() {
+                map.serialize_entry("backtrace", &frames)?;
+            }
+        }
+        #[cfg(feature = "build-info")]
+        map.serialize_entry("build", crate::build_info::build_info())?;
+        map.end()
+    }
+}
+
+#[cfg(feature = "backtrace")]
+impl Serialize for crate::backtrace::Frame 
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> 
+    {
+        let mut map = serializer.serialize_map(Some(3))?;
+        map.serialize_entry("function", &self.function)?;
+        map.serialize_entry("file", &self.file)?;
+        map.serialize_entry("line", &self.line)?;
+        map.end()
+    }
+}
+
+impl DebugError 
+{
+    /// Renders the error as a single line JSON object
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    ///
+    /// let err = debug_error!("Timeout").with_code("DB-001");
+    /// let json: serde_json::Value = serde_json::from_str(&err.to_json()).unwrap();
+    ///
+    /// assert_eq!(json["message"], "Timeout");
+    /// assert_eq!(json["file"], err.location.file());
+    /// assert_eq!(json["line"], err.location.line());
+    /// assert_eq!(json["code"], "DB-001");
+    /// assert_eq!(json["seq"], err.seq());
+    /// assert!(json.get("sources").is_none());
+    ///
+    /// let err = err.with_http_context("POST", "/orders");
+    /// let json: serde_json::Value = serde_json::from_str(&err.to_json()).unwrap();
+    /// assert_eq!(json["http"], serde_json::json!({"method": "POST", "path": "/orders", "status": null}));
+    /// ```
+    pub fn to_json(&self) -> String 
+    {
+        ::serde_json::to_string(self).expect("serializing a DebugError can't fail")
+    }
+
+    /// Renders the error as a single line JSON object with the field names of `config`
+    ///
+    /// Names with dots are nested, so `"log.origin.file.line"` becomes
+    /// `{"log":{"origin":{"file":{"line":42}}}}`. Fields without a configured name keep
+    /// the names of `to_json`. Keys are sorted alphabetically.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{debug_error, JsonConfig};
+    ///
+    /// let err = debug_error!("Timeout").with_code("DB-001");
+    /// let json: serde_json::Value = serde_json::from_str(&err.to_json_with(&JsonConfig::ecs())).unwrap();
+    ///
+    /// assert_eq!(json["error"]["message"], "Timeout");
+    /// assert_eq!(json["error"]["code"], "DB-001");
+    /// assert_eq!(json["log"]["origin"]["file"]["name"], err.location.file());
+    /// assert_eq!(json["log"]["origin"]["file"]["line"], err.location.line());
+    /// assert!(json.get("message").is_none() && json.get("column").is_none());
+    /// assert_eq!(json["seq"], err.seq());
+    ///
+    /// let json: serde_json::Value = serde_json::from_str(&err.to_json_with(&JsonConfig::bunyan())).unwrap();
+    /// assert_eq!(json["msg"], "Timeout");
+    /// assert_eq!(json["src"]["line"], err.location.line());
+    /// ```
+    pub fn to_json_with(&self, config: &JsonConfig) -> String 
+    {
+        let ::serde_json::Value::Object(mut fields) = ::serde_json::to_value(self).expect("serializing a DebugError can't fail")
+        else {
+            unreachable!("a DebugError serializes as a map");
+        };
+        let renames = [
+            ("message", Some(config.message)),
+            ("file", Some(config.file)),
+            ("line", Some(config.line)),
+            ("column", config.column),
+            ("code", Some(config.code)),
+        ];
+        let mut renamed = Vec::new();
+        for (field, name) in renames {
+            if let Some(value) = fields.remove(field) {
+                renamed.push((name, value));
+            }
+        }
+        for (name, value) in renamed.into_iter().filter_map(|(name, value)| Some((name?, value))) {
+            insert_nested(&mut fields, name, value);
+        }
+        ::serde_json::Value::Object(fields).to_string()
+    }
+}
+
+/// Field names for `DebugError::to_json_with`
+///
+/// The default uses the names of `to_json`. The presets match common log pipelines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonConfig 
+{
+    pub message: &'static str,
+    pub file: &'static str,
+    pub line: &'static str,
+    /// `None` leaves the column out
+    pub column: Option<&'static str>,
+    pub code: &'static str,
+}
+
+impl Default for JsonConfig 
+{
+    fn default() -> Self 
+    {
+        Self { message: "message", file: "file", line: "line", column: Some("column"), code: "code" }
+    }
+}
+
+impl JsonConfig 
+{
+    /// Elastic Common Schema: `error.message`, `error.code` and `log.origin.file.*`
+    pub fn ecs() -> Self 
+    {
+        Self {
+            message: "error.message",
+            file: "log.origin.file.name",
+            line: "log.origin.file.line",
+            column: None,
+            code: "error.code",
+        }
+    }
+
+    /// Bunyan: `msg` and the source location in `src`
+    pub fn bunyan() -> Self 
+    {
+        Self { message: "msg", file: "src.file", line: "src.line", column: None, code: "code" }
+    }
+}
+
+/// Inserts a value under a dotted name, creating the nested objects on the way
+fn insert_nested(fields: &mut ::serde_json::Map<String, ::serde_json::Value>, name: &str, value: ::serde_json::Value) 
+{
+    match name.split_once('.') {
+        None => {
+            fields.insert(name.to_string(), value);
+        }
+        Some((head, rest)) => {
+            let child = fields
+                .entry(head)
+                .or_insert_with(|| ::serde_json::Value::Object(::serde_json::Map::new()));
+            if !child.is_object() {
+                *child = ::serde_json::Value::Object(::serde_json::Map::new());
+            }
+            if let ::serde_json::Value::Object(child) = child {
+                insert_nested(child, rest, value);
+            }
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Coarse categories of errors, for mapping them to status codes or retry decisions
+
+/// What kind of failure an error is
+///
+/// Unlike codes, kinds are a small fixed set, so callers can `match` on them
+/// exhaustively, e.g. to pick an HTTP status.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error, Kind};
+///
+/// let err = debug_error!("User 7 not found").with_kind(Kind::NotFound);
+///
+/// assert_eq!(err.kind(), Some(Kind::NotFound));
+/// assert_eq!(Kind::NotFound.to_string(), "not found");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind 
+{
+    /// The requested thing doesn't exist
+    NotFound,
+    /// The caller isn't allowed to do this
+    PermissionDenied,
+    /// The input was malformed or out of range
+    InvalidInput,
+    /// The operation took too long
+    Timeout,
+    /// The operation clashes with the current state, e.g. a duplicate key
+    Conflict,
+    /// A dependency is temporarily down, retrying may help
+    Unavailable,
+    /// A bug or an unexpected failure
+    Internal,
+}
+
+impl Kind 
+{
+    /// Returns the kind as a lowercase, human readable string
+    pub fn as_str(&self) -> &'static str 
+    {
+        match self {
+            Kind::NotFound => "not found",
+            Kind::PermissionDenied => "permission denied",
+            Kind::InvalidInput => "invalid input",
+            Kind::Timeout => "timeout",
+            Kind::Conflict => "conflict",
+            Kind::Unavailable => "unavailable",
+            Kind::Internal => "internal",
+        }
+    }
+
+    /// Returns the kind as an uppercase tag, e.g. `NOT_FOUND`
+    ///
+    /// With the `kind-prefix` feature, `Display` and `debug_error_with_log!` put the
+    /// tag in brackets before the message of errors with a kind, which makes kinds easy
+    /// to spot when scanning logs.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{debug_error, Kind};
+    ///
+    /// assert_eq!(Kind::Timeout.as_tag(), "TIMEOUT");
+    ///
+    /// let err = debug_error!("Upstream took 30s").with_kind(Kind::Timeout);
+    /// # #[cfg(feature = "kind-prefix")]
+    /// assert!(err.to_string().starts_with("[TIMEOUT] Upstream took 30s at "));
+    /// # #[cfg(not(feature = "kind-prefix"))]
+    /// assert!(err.to_string().starts_with("Upstream took 30s at "));
+    ///
+    /// // Errors without a kind never get a prefix
+    /// assert!(debug_error!("Upstream took 30s").to_string().starts_with("Upstream took 30s at "));
+    /// ```
+    pub fn as_tag(&self) -> &'static str 
+    {
+        match self {
+            Kind::NotFound => "NOT_FOUND",
+            Kind::PermissionDenied => "PERMISSION_DENIED",
+            Kind::InvalidInput => "INVALID_INPUT",
+            Kind::Timeout => "TIMEOUT",
+            Kind::Conflict => "CONFLICT",
+            Kind::Unavailable => "UNAVAILABLE",
+            Kind::Internal => "INTERNAL",
+        }
+    }
+
+    /// Returns the HTTP status that fits the kind, e.g. 404 for `NotFound`
+    pub fn http_status(&self) -> u16 
+    {
+        match self {
+            Kind::NotFound => 404,
+            Kind::PermissionDenied => 403,
+            Kind::InvalidInput => 400,
+            Kind::Timeout => 504,
+            Kind::Conflict => 409,
+            Kind::Unavailable => 503,
+            Kind::Internal => 500,
+        }
+    }
+
+    /// Returns the `io::ErrorKind` that fits the kind, `Other` if none does
+    pub fn io_error_kind(&self) -> std::io::ErrorKind 
+    {
+        match self {
+            Kind::NotFound => std::io::ErrorKind::NotFound,
+            Kind::PermissionDenied => std::io::ErrorKind::PermissionDenied,
+            Kind::InvalidInput => std::io::ErrorKind::InvalidInput,
+            Kind::Timeout => std::io::ErrorKind::TimedOut,
+            Kind::Conflict | Kind::Unavailable | Kind::Internal => std::io::ErrorKind::Other,
+        }
+    }
+}
+
+impl std::fmt::Display for Kind 
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result 
+    {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Generates `fn kind_to_code(kind: Kind) -> &'static str` from a table of all kinds
+///
+/// The table becomes an exhaustive `match`, so forgetting a kind, or adding a kind to
+/// `Kind` later, is a compile error instead of a silently unmapped code.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error, error_code_table, Kind};
+///
+/// error_code_table! {
+///     NotFound => "E404",
+///     PermissionDenied => "E403",
+///     InvalidInput => "E400",
+///     Timeout => "E504",
+///     Conflict => "E409",
+///     Unavailable => "E503",
+///     Internal => "E500",
+/// }
+///
+/// assert_eq!(kind_to_code(Kind::Conflict), "E409");
+///
+/// let kind = Kind::NotFound;
+/// let err = debug_error!("Order 7 not found").with_kind(kind).with_code(kind_to_code(kind));
+/// assert_eq!(err.code(), Some("E404"));
+/// ```
+///
+/// A table missing a kind doesn't compile:
+///
+/// ```compile_fail,E0004
+/// use debug_error::error_code_table;
+///
+/// error_code_table! {
+///     NotFound => "E404",
+///     InvalidInput => "E400",
+/// }
+/// ```
+#[macro_export]
+macro_rules! error_code_table
+{
+    ($($kind:ident => $code:expr),+ $(,)?) => {
+        fn kind_to_code(kind: $crate::Kind) -> &'static str 
+        {
+            match kind {
+                $($crate::Kind::$kind => $code,)+
+            }
+        }
+    };
+}
@@ -1,2 +1,134 @@
 pub mod error;
 pub use crate::error::*;
+
+pub mod config;
+pub use crate::config::*;
+
+pub mod location;
+pub use crate::location::*;
+
+pub mod context;
+pub use crate::context::*;
+
+pub mod stats;
+pub use crate::stats::*;
+
+pub mod channel;
+pub use crate::channel::*;
+
+pub mod subscriber;
+pub use crate::subscriber::*;
+
+pub mod logging;
+pub use crate::logging::*;
+
+pub mod codes;
+pub use crate::codes::*;
+
+pub mod kind;
+pub use crate::kind::*;
+
+pub mod result_ext;
+pub use crate::result_ext::*;
+
+pub mod lock;
+pub use crate::lock::*;
+
+pub mod panic;
+pub use crate::panic::*;
+
+pub mod marker;
+pub use crate::marker::*;
+
+pub mod static_error;
+pub use crate::static_error::*;
+
+pub mod template;
+pub use crate::template::*;
+
+pub mod report;
+pub use crate::report::*;
+
+pub mod aggregate;
+pub use crate::aggregate::*;
+
+pub mod retry;
+pub use crate::retry::*;
+
+pub mod checked;
+pub use crate::checked::*;
+
+pub mod pretty;
+pub use crate::pretty::*;
+
+pub mod gha;
+
+#[cfg(feature = "serde")]
+pub mod json;
+#[cfg(feature = "serde")]
+pub use crate::json::*;
+
+#[cfg(feature = "env-snapshot")]
+pub mod env_snapshot;
+#[cfg(feature = "env-snapshot")]
+pub use crate::env_snapshot::*;
+
+#[cfg(feature = "breadcrumbs")]
+pub mod breadcrumbs;
+#[cfg(feature = "breadcrumbs")]
+pub use crate::breadcrumbs::*;
+
+#[cfg(feature = "resource-snapshot")]
+pub mod resources;
+#[cfg(feature = "resource-snapshot")]
+pub use crate::resources::*;
+
+#[cfg(feature = "sentry")]
+pub mod sentry;
+#[cfg(feature = "sentry")]
+pub use crate::sentry::*;
+
+#[cfg(all(feature = "journald", target_os = "linux"))]
+pub mod journald;
+
+#[cfg(feature = "otel")]
+pub mod otel;
+#[cfg(feature = "otel")]
+pub use crate::otel::*;
+
+#[cfg(feature = "tonic")]
+pub mod tonic;
+
+
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "testing")]
+pub use crate::testing::*;
+
+#[cfg(feature = "axum")]
+pub mod axum;
+
+#[cfg(feature = "tracing")]
+pub mod tracing;
+#[cfg(feature = "tracing")]
+pub use crate::tracing::*;
+
+#[cfg(feature = "build-info")]
+pub mod build_info;
+#[cfg(feature = "build-info")]
+pub use crate::build_info::*;
+
+#[cfg(feature = "backtrace")]
+pub mod backtrace;
+#[cfg(feature = "backtrace")]
+pub use crate::backtrace::*;
+
+#[cfg(feature = "simple-logger")]
+pub mod simple_logger;
+#[cfg(feature = "simple-logger")]
+pub use crate::simple_logger::*;
+
+#[cfg(feature = "file-sink")]
+pub mod file_sink;
+#[cfg(feature = "file-sink")]
+pub use crate::file_sink::*;
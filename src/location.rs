@@ -0,0 +1,66 @@
+//! Source location of an error
+//!
+//! `LocationInfo` holds the same information as `std::panic::Location`, but can also
+//! own its file path. That allows locations that weren't captured by the compiler,
+//! e.g. overridden locations for generated code or locations received from another
+//! process.
+
+use std::borrow::Cow;
+use std::panic::Location;
+
+use crate::config::{show_column, trim_path};
+
+/// File, line and column where an error was created
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LocationInfo 
+{
+    file: Cow<'static, str>,
+    line: u32,
+    column: u32,
+}
+
+impl LocationInfo 
+{
+    pub fn new(file: impl Into<Cow<'static, str>>, line: u32, column: u32) -> Self 
+    {
+        Self { file: file.into(), line, column }
+    }
+
+    /// Returns the source file path
+    pub fn file(&self) -> &str 
+    {
+        &self.file
+    }
+
+    /// Returns the line number, starting at 1
+    pub fn line(&self) -> u32 
+    {
+        self.line
+    }
+
+    /// Returns the column number, starting at 1
+    pub fn column(&self) -> u32 
+    {
+        self.column
+    }
+}
+
+impl From<&'static Location<'static>> for LocationInfo 
+{
+    fn from(location: &'static Location<'static>) -> Self 
+    {
+        Self::new(location.file(), location.line(), location.column())
+    }
+}
+
+impl std::fmt::Display for LocationInfo 
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result 
+    {
+        write!(f, "{}:{}", trim_path(&self.file), self.line)?;
+        if show_column() {
+            write!(f, ":{}", self.column)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+//! Conversion of lock poisoning into `DebugError`
+
+use std::sync::LockResult;
+
+use crate::DebugError;
+
+/// Converts the result of locking a `Mutex` or `RwLock` into a `DebugError` result
+///
+/// A `PoisonError` holds the lock guard, which is neither `Send` nor `'static`, so it
+/// can't be kept as a source. On poison the guard is dropped, releasing the lock, and
+/// an error with the given message and the caller location is returned instead.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{lock_err, DebugError};
+/// use std::sync::{Arc, Mutex};
+///
+/// let cache = Arc::new(Mutex::new(vec![1, 2, 3]));
+///
+/// // A thread panics while holding the lock, which poisons the mutex
+/// let worker_cache = Arc::clone(&cache);
+/// let _ = std::thread::spawn(move || {
+///     let _guard = worker_cache.lock().unwrap();
+///     panic!("worker crashed");
+/// })
+/// .join();
+///
+/// fn cache_len(cache: &Mutex<Vec<u32>>) -> Result<usize, DebugError> {
+///     let guard = lock_err(cache.lock(), "Cache lock poisoned")?;
+///     Ok(guard.len())
+/// }
+///
+/// let err = cache_len(&cache).unwrap_err();
+/// assert_eq!(err.message, "Cache lock poisoned");
+///
+/// // Once the data is known to be fine again, clear the poison and carry on
+/// cache.clear_poison();
+/// assert_eq!(cache_len(&cache).unwrap(), 3);
+/// ```
+#[track_caller]
+pub fn lock_err<T>(result: LockResult<T>, msg: &str) -> Result<T, DebugError> 
+{
+    match result {
+        Ok(guard) => Ok(guard),
+        Err(_) => Err(DebugError::new(msg.to_string(), std::panic::Location::caller())),
+    }
+}
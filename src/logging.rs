@@ -0,0 +1,506 @@
+//! Logging of errors created with `debug_error_with_log!`
+//!
+//! The macro hands the new error to this module, which applies the logging policies
+//! and emits the record. The record keeps the module of the macro call as its target
+//! (unless its category is mapped to another one) and the location of the error as
+//! its file and line, like a `log::error!` written at the call site would.
+
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::RwLock;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use ::log::{Level, Record};
+
+use crate::DebugError;
+
+static PRE_LOG_TRANSFORM: RwLock<fn(DebugError) -> DebugError> = RwLock::new(identity);
+static BATCH_SENDER: RwLock<Option<(u64, SyncSender<Emission>)>> = RwLock::new(None);
+static BATCH_GENERATION: AtomicU64 = AtomicU64::new(0);
+static SUPPRESSED_CODES: RwLock<Vec<String>> = RwLock::new(Vec::new());
+static CATEGORY_TARGETS: RwLock<Vec<(&'static str, &'static str)>> = RwLock::new(Vec::new());
+static MAX_LOGGED_ERRORS: AtomicUsize = AtomicUsize::new(usize::MAX);
+static LOGGED_ERRORS: AtomicUsize = AtomicUsize::new(0);
+
+fn identity(err: DebugError) -> DebugError 
+{
+    err
+}
+
+/// Sets a function that transforms errors of `debug_error_with_log!` before logging
+///
+/// Use it to enrich errors right before they are logged, e.g. with a request id from
+/// a thread-local. The transformed error is logged and returned by the macro. Errors
+/// created with `debug_error!` are never passed to the transform. The default is the
+/// identity.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error_with_log, set_pre_log_transform, DebugError};
+/// # debug_error::__capture_logs();
+///
+/// fn tag_request(mut err: DebugError) -> DebugError {
+///     err.message.to_mut().push_str(" [request 7f3a]");
+///     err
+/// }
+///
+/// set_pre_log_transform(tag_request);
+/// let err = debug_error_with_log!("Checkout failed");
+///
+/// assert_eq!(err.message, "Checkout failed [request 7f3a]");
+/// let records = debug_error::__captured_logs();
+/// assert!(records[0].message.starts_with("Error: Checkout failed [request 7f3a] at "));
+/// ```
+pub fn set_pre_log_transform(transform: fn(DebugError) -> DebugError) 
+{
+    *PRE_LOG_TRANSFORM.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = transform;
+}
+
+/// Sets the error codes `debug_error_with_log!` doesn't log
+///
+/// Mutes known-noisy errors without touching their call sites: the macro still
+/// creates and returns the error, it just skips the log record. Replaces the codes of
+/// earlier calls, an empty slice logs everything again.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{declare_error_codes, debug_error_with_log, set_suppressed_codes};
+/// # debug_error::__capture_logs();
+///
+/// declare_error_codes! {
+///     DB_TIMEOUT = "DB-TIMEOUT",
+///     DB_DEADLOCK = "DB-DEADLOCK",
+/// }
+///
+/// set_suppressed_codes(&["DB-TIMEOUT"]);
+///
+/// let err = debug_error_with_log!(code = DB_TIMEOUT, "Query took longer than 5s");
+/// assert_eq!(err.code(), Some("DB-TIMEOUT"));
+/// assert!(debug_error::__captured_logs().is_empty());
+///
+/// debug_error_with_log!(code = DB_DEADLOCK, "Deadlock detected");
+/// assert_eq!(debug_error::__captured_logs().len(), 1);
+/// ```
+pub fn set_suppressed_codes(codes: &[&str]) 
+{
+    *SUPPRESSED_CODES.write().unwrap_or_else(|poisoned| poisoned.into_inner()) =
+        codes.iter().map(|code| code.to_string()).collect();
+}
+
+fn is_suppressed(err: &DebugError) -> bool 
+{
+    let Some(code) = err.code() else {
+        return false;
+    };
+    SUPPRESSED_CODES.read().unwrap_or_else(|poisoned| poisoned.into_inner()).iter().any(|suppressed| suppressed == code)
+}
+
+/// Caps how many errors `debug_error_with_log!` logs, as a safety valve for runaway loops
+///
+/// Once `max` errors were logged, one final warning says that the limit was reached
+/// and all further errors are created and returned as usual, but not logged. Setting
+/// a limit starts counting from zero again. There is no limit by default.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error_with_log, set_max_logged_errors};
+/// # debug_error::__capture_logs();
+///
+/// set_max_logged_errors(3);
+///
+/// for i in 0..10 {
+///     let err = debug_error_with_log!("Row {} is corrupt", i);
+///     assert_eq!(err.message, format!("Row {} is corrupt", i));
+/// }
+///
+/// let records = debug_error::__captured_logs();
+/// assert_eq!(records.len(), 4);
+/// assert!(records[2].message.starts_with("Error: Row 2 is corrupt at "));
+/// assert_eq!(records[3].level, log::Level::Warn);
+/// assert_eq!(records[3].message, "Log limit reached: 3 errors logged, suppressing further errors");
+/// ```
+pub fn set_max_logged_errors(max: usize) 
+{
+    MAX_LOGGED_ERRORS.store(max, Ordering::Relaxed);
+    LOGGED_ERRORS.store(0, Ordering::Relaxed);
+}
+
+/// Counts an error against the limit of `set_max_logged_errors`, false if over it
+fn within_log_limit(err: &DebugError, module_path: &'static str) -> bool 
+{
+    let max = MAX_LOGGED_ERRORS.load(Ordering::Relaxed);
+    if max == usize::MAX {
+        return true;
+    }
+    let logged = LOGGED_ERRORS.fetch_add(1, Ordering::Relaxed);
+    if logged == max {
+        emit(Emission {
+            level: Level::Warn,
+            target: module_path,
+            module_path,
+            file: err.location.file().to_string(),
+            line: err.location.line(),
+            message: format!("Log limit reached: {} errors logged, suppressing further errors", max),
+            fields: Vec::new(),
+        });
+    }
+    logged < max
+}
+
+/// Maps error categories to the log targets `debug_error_with_log!` uses for them
+///
+/// Lets loggers route errors by subsystem, e.g. into one file per target. Errors
+/// without a category, or with an unmapped one, keep the module of the macro call as
+/// their target. Replaces the map of earlier calls.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error_with_log, set_category_target_map};
+/// # debug_error::__capture_logs();
+///
+/// set_category_target_map(&[("db", "app::storage"), ("http", "app::web")]);
+///
+/// debug_error_with_log!(category = "db", "Connection pool exhausted");
+/// debug_error_with_log!(category = "http", "Upstream returned 502");
+/// debug_error_with_log!("No category");
+///
+/// let targets: Vec<String> = debug_error::__captured_logs().into_iter().map(|record| record.target).collect();
+/// assert_eq!(targets, ["app::storage", "app::web", module_path!()]);
+/// ```
+pub fn set_category_target_map(map: &[(&'static str, &'static str)]) 
+{
+    *CATEGORY_TARGETS.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = map.to_vec();
+}
+
+/// Returns the target for an error, the mapped one of its category or the module
+fn target_for(err: &DebugError, module_path: &'static str) -> &'static str 
+{
+    let Some(category) = err.category() else {
+        return module_path;
+    };
+    CATEGORY_TARGETS
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .iter()
+        .find(|(mapped, _)| *mapped == category)
+        .map_or(module_path, |(_, target)| *target)
+}
+
+/// Starts emitting the records of `debug_error_with_log!` in batches on a background thread
+///
+/// The macro then only hands the record over to a channel, which takes pressure off
+/// hot paths when the logger is slow. Records are flushed when `capacity` records are
+/// collected or `flush_interval` has passed since the last flush. The channel is
+/// bounded by `capacity` too: when the background thread falls behind, the macro
+/// waits instead of dropping records.
+///
+/// Dropping the returned guard flushes the remaining records and stops the thread,
+/// so keep it alive until the end of `main`. Starting another batched logger replaces
+/// the running one, which flushes and stops on its own, dropping its guard later
+/// leaves the new logger alone.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error_with_log, start_batched_logger};
+/// use std::time::Duration;
+/// # debug_error::__capture_logs();
+///
+/// let guard = start_batched_logger(4, Duration::from_secs(60));
+/// for i in 0..10 {
+///     debug_error_with_log!("Job {} failed", i);
+/// }
+/// drop(guard);
+///
+/// let records = debug_error::__captured_logs();
+/// assert_eq!(records.len(), 10);
+/// assert!(records[9].message.starts_with("Error: Job 9 failed at "));
+/// ```
+///
+/// The guard of a replaced logger doesn't stop its successor:
+///
+/// ```rust
+/// use debug_error::{debug_error_with_log, start_batched_logger};
+/// use std::time::Duration;
+/// # debug_error::__capture_logs();
+///
+/// let old = start_batched_logger(100, Duration::from_secs(60));
+/// debug_error_with_log!("Sent to the old logger");
+/// let new = start_batched_logger(100, Duration::from_secs(60));
+/// drop(old);
+/// assert_eq!(debug_error::__captured_logs().len(), 1);
+///
+/// // Still batched by the new logger, so only logged once it is dropped
+/// debug_error_with_log!("Sent to the new logger");
+/// assert_eq!(debug_error::__captured_logs().len(), 1);
+/// drop(new);
+///
+/// let records = debug_error::__captured_logs();
+/// assert_eq!(records.len(), 2);
+/// assert!(records[1].message.starts_with("Error: Sent to the new logger at "));
+/// ```
+pub fn start_batched_logger(capacity: usize, flush_interval: Duration) -> BatchedLoggerGuard 
+{
+    let capacity = capacity.max(1);
+    // A zero interval would make the thread spin
+    let flush_interval = flush_interval.max(Duration::from_millis(1));
+    let (sender, receiver) = mpsc::sync_channel::<Emission>(capacity);
+    let thread = std::thread::Builder::new()
+        .name("debug_error-batched-logger".to_string())
+        .spawn(move || {
+            let mut batch = Vec::with_capacity(capacity);
+            let mut last_flush = Instant::now();
+            loop {
+                let timeout = flush_interval.saturating_sub(last_flush.elapsed());
+                let disconnected = match receiver.recv_timeout(timeout) {
+                    Ok(emission) => {
+                        batch.push(emission);
+                        false
+                    }
+                    Err(RecvTimeoutError::Timeout) => false,
+                    Err(RecvTimeoutError::Disconnected) => true,
+                };
+                if disconnected || batch.len() >= capacity || last_flush.elapsed() >= flush_interval {
+                    flush(&mut batch);
+                    last_flush = Instant::now();
+                }
+                if disconnected {
+                    break;
+                }
+            }
+        })
+        .expect("failed to spawn the batched logger thread");
+    let generation = BATCH_GENERATION.fetch_add(1, Ordering::Relaxed);
+    *BATCH_SENDER.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some((generation, sender));
+    BatchedLoggerGuard { generation, thread: Some(thread) }
+}
+
+/// Guard returned by `start_batched_logger`, flushes and stops the logger when dropped
+#[must_use = "the batched logger stops as soon as the guard is dropped"]
+#[derive(Debug)]
+pub struct BatchedLoggerGuard 
+{
+    /// Tells the sender of this logger apart from the one of a logger started later
+    generation: u64,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for BatchedLoggerGuard 
+{
+    fn drop(&mut self) 
+    {
+        // Dropping the sender disconnects the channel, which makes the thread flush and exit.
+        // A replaced logger lost its sender already, the slot belongs to its successor.
+        let mut sender = BATCH_SENDER.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if sender.as_ref().is_some_and(|(generation, _)| *generation == self.generation) {
+            sender.take();
+        }
+        drop(sender);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// A record waiting in the batch channel
+#[derive(Clone)]
+struct Emission 
+{
+    level: Level,
+    target: &'static str,
+    module_path: &'static str,
+    file: String,
+    line: u32,
+    message: String,
+    /// The key-value pairs of the error, logged as structured fields
+    fields: Vec<(&'static str, String)>,
+}
+
+fn flush(batch: &mut Vec<Emission>) 
+{
+    if batch.is_empty() {
+        return;
+    }
+    for emission in batch.drain(..) {
+        log_record(&emission);
+    }
+    ::log::logger().flush();
+}
+
+fn log_record(emission: &Emission) 
+{
+    #[cfg(feature = "file-sink")]
+    if crate::file_sink::write(emission.level, &emission.message) {
+        return;
+    }
+    let fields: Vec<(&str, &str)> = emission.fields.iter().map(|(key, value)| (*key, value.as_str())).collect();
+    ::log::logger().log(
+        &Record::builder()
+            .args(format_args!("{}", emission.message))
+            .level(emission.level)
+            .target(emission.target)
+            .module_path_static(Some(emission.module_path))
+            .file(Some(&emission.file))
+            .line(Some(emission.line))
+            .key_values(&fields)
+            .build(),
+    );
+}
+
+/// Logs a new error of `debug_error_with_log!` and returns it
+#[doc(hidden)]
+pub fn __log_created(err: DebugError, module_path: &'static str) -> DebugError 
+{
+    let transform = *PRE_LOG_TRANSFORM.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let err = transform(err);
+    #[cfg(feature = "otel")]
+    if crate::otel::otel_autorecord() {
+        err.record_on_span();
+    }
+    let message = format!("Error: {}{}{}", err.display_message(), crate::config::location_separator(), err.location);
+    log_error(&err, module_path, message);
+    err
+}
+
+/// Logs an error at its level, if the level is enabled and the policies allow it
+///
+/// Applies the suppressed codes and the log limit and marks the error as logged. The
+/// record gets `module_path` (or the target of the category) as its target and the
+/// location of the error as its file and line.
+pub(crate) fn log_error(err: &DebugError, module_path: &'static str, message: String) 
+{
+    let level = err.level();
+    // The error log file takes every level, it isn't part of the `log` framework
+    #[cfg(feature = "file-sink")]
+    let enabled = level <= ::log::max_level() || crate::file_sink::active();
+    #[cfg(not(feature = "file-sink"))]
+    let enabled = level <= ::log::max_level();
+    if !enabled || is_suppressed(err) || !within_log_limit(err, module_path) {
+        return;
+    }
+    err.mark_logged();
+    // Annotations are read from stdout, the logger output may go anywhere
+    if crate::gha::github_actions() {
+        println!("{}", err.render_gha());
+    }
+    let emission = Emission {
+        level,
+        target: target_for(err, module_path),
+        module_path,
+        file: err.location.file().to_string(),
+        line: err.location.line(),
+        message,
+        fields: err.kv().to_vec(),
+    };
+    #[cfg(feature = "fold-repeats")]
+    let Some(emission) = fold::fold(emission) else {
+        return;
+    };
+    emit(emission);
+}
+
+/// Hands a record to the batch thread if one is running, otherwise logs it directly
+fn emit(emission: Emission) 
+{
+    let sender = BATCH_SENDER.read().unwrap_or_else(|poisoned| poisoned.into_inner()).as_ref().map(|(_, sender)| sender.clone());
+    let emission = match sender {
+        // Only fails if the logger was stopped in the meantime, then log directly
+        Some(sender) => match sender.send(emission) {
+            Ok(()) => return,
+            Err(mpsc::SendError(emission)) => emission,
+        },
+        None => emission,
+    };
+    log_record(&emission);
+}
+
+#[cfg(feature = "fold-repeats")]
+pub use self::fold::flush_repeats;
+
+/// Folding of repeated records, enabled with the `fold-repeats` feature
+#[cfg(feature = "fold-repeats")]
+mod fold 
+{
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use super::{emit, Emission};
+
+    /// Last record and number of folded repeats, keyed by file and line of the call site
+    type LastEmissions = HashMap<(String, u32), (Emission, u32)>;
+
+    static LAST_EMISSIONS: Mutex<Option<LastEmissions>> = Mutex::new(None);
+
+    /// Returns the record if it has to be emitted, `None` if it was folded
+    ///
+    /// A record that differs from the last one of its call site first emits the
+    /// summary of the repeats of the last one.
+    pub(super) fn fold(emission: Emission) -> Option<Emission> 
+    {
+        let mut last_emissions = LAST_EMISSIONS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let last_emissions = last_emissions.get_or_insert_with(HashMap::new);
+        let site = (emission.file.clone(), emission.line);
+        if let Some((last, repeats)) = last_emissions.get_mut(&site) {
+            if last.message == emission.message {
+                *repeats += 1;
+                return None;
+            }
+            if *repeats > 0 {
+                emit(summary(last, *repeats));
+            }
+        }
+        last_emissions.insert(site, (emission.clone(), 0));
+        Some(emission)
+    }
+
+    fn summary(last: &Emission, repeats: u32) -> Emission 
+    {
+        let mut summary = last.clone();
+        summary.message = format!("{} (repeated {} times)", last.message, repeats);
+        summary
+    }
+
+    /// Emits the summaries of all folded repeats
+    ///
+    /// With the `fold-repeats` feature, `debug_error_with_log!` only logs the first of
+    /// several identical records in a row from the same call site. The repeats are
+    /// summarized as "<record> (repeated N times)" when the call site logs something
+    /// else, or when calling this function, e.g. periodically or before exiting.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{debug_error_with_log, flush_repeats};
+    /// # debug_error::__capture_logs();
+    ///
+    /// for _ in 0..16 {
+    ///     let _ = debug_error_with_log!("Sensor 3 offline");
+    /// }
+    /// assert_eq!(debug_error::__captured_logs().len(), 1);
+    ///
+    /// flush_repeats();
+    ///
+    /// let records = debug_error::__captured_logs();
+    /// assert_eq!(records.len(), 2);
+    /// assert!(records[0].message.starts_with("Error: Sensor 3 offline at "));
+    /// assert_eq!(records[1].message, format!("{} (repeated 15 times)", records[0].message));
+    /// ```
+    pub fn flush_repeats() 
+    {
+        let mut last_emissions = LAST_EMISSIONS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let Some(last_emissions) = last_emissions.as_mut() else {
+            return;
+        };
+        for (last, repeats) in last_emissions.values_mut() {
+            if *repeats > 0 {
+                emit(summary(last, *repeats));
+                *repeats = 0;
+            }
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Copyable location tokens that turn into errors later
+
+use std::panic::Location;
+
+use crate::DebugError;
+
+/// The location of an error, without anything else
+///
+/// Creating a marker costs nothing beyond copying a pointer, so it fits hot paths
+/// where even formatting a message is too expensive, or where the message is only
+/// known higher up the stack. `inflate` turns it into a `DebugError` that points at
+/// the place the marker was created. Created with `debug_marker!`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DebugMarker 
+{
+    location: &'static Location<'static>,
+}
+
+impl DebugMarker 
+{
+    /// Creates a marker of the caller location, see `debug_marker!`
+    #[track_caller]
+    pub fn new() -> Self 
+    {
+        Self { location: Location::caller() }
+    }
+
+    /// Returns the location the marker was created at
+    pub fn location(&self) -> &'static Location<'static> 
+    {
+        self.location
+    }
+
+    /// Creates an error with the message, located where the marker was created
+    pub fn inflate(self, message: impl Into<String>) -> DebugError 
+    {
+        let message: String = message.into();
+        DebugError::new(message, self.location)
+    }
+}
+
+impl Default for DebugMarker 
+{
+    #[track_caller]
+    fn default() -> Self 
+    {
+        Self::new()
+    }
+}
+
+/// Creates a `DebugMarker` of the current location
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_marker, DebugError, DebugMarker};
+///
+/// // Hot path: only remember where parsing failed
+/// fn parse_digit(byte: u8) -> Result<u8, DebugMarker> {
+///     if byte.is_ascii_digit() { Ok(byte - b'0') } else { Err(debug_marker!()) }
+/// }
+/// let marker_line = line!() - 2;
+///
+/// // Boundary: the message is known here
+/// fn parse_pin(pin: &str) -> Result<Vec<u8>, DebugError> {
+///     pin.bytes()
+///         .map(parse_digit)
+///         .collect::<Result<_, _>>()
+///         .map_err(|marker| marker.inflate(format!("Invalid PIN {:?}", pin)))
+/// }
+///
+/// assert_eq!(parse_pin("1234").unwrap(), [1, 2, 3, 4]);
+///
+/// let err = parse_pin("12a4").unwrap_err();
+/// assert_eq!(err.message, "Invalid PIN \"12a4\"");
+/// assert_eq!(err.location.line(), marker_line);
+/// ```
+#[macro_export]
+macro_rules! debug_marker
+{
+    () => {
+        $crate::DebugMarker::new()
+    };
+}
@@ -0,0 +1,115 @@
+//! OpenTelemetry integration, enabled with the `otel` feature
+//!
+//! Records errors as `exception` events on the active span, with the attributes of the
+//! OpenTelemetry semantic conventions for exceptions and source code.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use ::opentelemetry::trace::get_active_span;
+use ::opentelemetry::KeyValue;
+
+use crate::DebugError;
+
+static AUTORECORD: AtomicBool = AtomicBool::new(false);
+
+/// Records every error of `debug_error_with_log!` on the active span when enabled
+///
+/// Disabled by default.
+pub fn set_otel_autorecord(enabled: bool) 
+{
+    AUTORECORD.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn otel_autorecord() -> bool 
+{
+    AUTORECORD.load(Ordering::Relaxed)
+}
+
+impl DebugError 
+{
+    /// Returns the attributes of the `exception` span event
+    ///
+    /// `exception.type` is the code of the error, or `DebugError` without one. The
+    /// key-value pairs of `with_kv` follow as attributes of their own.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    /// use opentelemetry::KeyValue;
+    ///
+    /// let err = debug_error!("Payment declined").with_kv("order_id", 1832);
+    /// let attributes = err.otel_attributes();
+    ///
+    /// assert!(attributes.contains(&KeyValue::new("exception.message", "Payment declined")));
+    /// assert!(attributes.contains(&KeyValue::new("order_id", "1832")));
+    /// ```
+    pub fn otel_attributes(&self) -> Vec<KeyValue> 
+    {
+        let mut attributes = vec![
+            KeyValue::new("exception.message", self.message.clone()),
+            KeyValue::new("exception.type", self.code().unwrap_or("DebugError")),
+            KeyValue::new("code.filepath", self.location.file().to_string()),
+            KeyValue::new("code.lineno", i64::from(self.location.line())),
+            KeyValue::new("code.column", i64::from(self.location.column())),
+        ];
+        attributes.extend(self.kv().iter().map(|(key, value)| KeyValue::new(*key, value.clone())));
+        attributes
+    }
+
+    /// Records the error as an `exception` event on the active span
+    ///
+    /// Does nothing if there is no active span.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::debug_error;
+    /// use opentelemetry::trace::{TraceContextExt, SpanContext, TraceId, SpanId, TraceFlags, TraceState};
+    /// use opentelemetry::{Context, KeyValue, Value};
+    /// use std::sync::{Arc, Mutex};
+    /// # use std::borrow::Cow;
+    /// # use std::time::SystemTime;
+    /// # #[derive(Debug, Clone, Default)]
+    /// # struct RecordingSpan { events: Arc<Mutex<Vec<(String, Vec<KeyValue>)>>> }
+    /// # impl opentelemetry::trace::Span for RecordingSpan {
+    /// #     fn add_event_with_timestamp<T: Into<Cow<'static, str>>>(&mut self, name: T, _: SystemTime, attributes: Vec<KeyValue>) {
+    /// #         self.events.lock().unwrap().push((name.into().into_owned(), attributes));
+    /// #     }
+    /// #     fn span_context(&self) -> &SpanContext {
+    /// #         static CONTEXT: std::sync::OnceLock<SpanContext> = std::sync::OnceLock::new();
+    /// #         CONTEXT.get_or_init(|| SpanContext::new(TraceId::from(1), SpanId::from(1), TraceFlags::SAMPLED, false, TraceState::default()))
+    /// #     }
+    /// #     fn is_recording(&self) -> bool { true }
+    /// #     fn set_attribute(&mut self, _: KeyValue) {}
+    /// #     fn set_status(&mut self, _: opentelemetry::trace::Status) {}
+    /// #     fn update_name<T: Into<Cow<'static, str>>>(&mut self, _: T) {}
+    /// #     fn add_link(&mut self, _: SpanContext, _: Vec<KeyValue>) {}
+    /// #     fn end_with_timestamp(&mut self, _: SystemTime) {}
+    /// # }
+    ///
+    /// // Any span from your tracer works, this one just records its events
+    /// let span = RecordingSpan::default();
+    /// let events = span.events.clone();
+    /// let _active = Context::current_with_span(span).attach();
+    ///
+    /// let err = debug_error!("Payment provider unavailable").with_code("PAY-503");
+    /// err.record_on_span();
+    ///
+    /// let events = events.lock().unwrap();
+    /// assert_eq!(events[0].0, "exception");
+    /// let attributes = &events[0].1;
+    /// assert!(attributes.contains(&KeyValue::new("exception.message", "Payment provider unavailable")));
+    /// assert!(attributes.contains(&KeyValue::new("exception.type", "PAY-503")));
+    /// assert!(attributes.contains(&KeyValue::new("code.lineno", i64::from(err.location.line()))));
+    /// ```
+    pub fn record_on_span(&self) 
+    {
+        get_active_span(|span| {
+            if span.span_context().is_valid() {
+                span.add_event("exception", self.otel_attributes());
+            }
+        });
+    }
+}
+
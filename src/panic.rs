@@ -0,0 +1,86 @@
+//! Conversion of caught panics into `DebugError`
+
+use std::cell::{Cell, RefCell};
+use std::panic::{AssertUnwindSafe, Location};
+use std::sync::Once;
+
+use crate::DebugError;
+
+thread_local! {
+    /// Nesting depth of `catch` calls on this thread
+    static CATCH_DEPTH: Cell<usize> = const { Cell::new(0) };
+    /// File, line and column of the last panic caught by `catch` on this thread
+    static PANIC_LOCATION: RefCell<Option<(String, u32, u32)>> = const { RefCell::new(None) };
+}
+
+static INSTALL_HOOK: Once = Once::new();
+
+/// Runs a closure and converts a panic into a `DebugError`
+///
+/// Meant for FFI and plugin boundaries, where a panic must not unwind any further.
+/// The error gets the panic message and the location of the panic, which a panic hook
+/// records. The hook is installed on the first call and wraps the previous one, which
+/// is still called for panics outside of `catch`. Panics inside `catch` aren't printed,
+/// they become the returned error instead. Without a location, e.g. when another hook
+/// replaced ours, the error points at the `catch` call.
+///
+/// Like with `catch_unwind`, the closure shouldn't leave shared state half updated
+/// when it panics, because the caller carries on with it.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::catch;
+///
+/// fn parse_plugin_output(values: &[u32]) -> u32 {
+///     values[3]
+/// }
+///
+/// let err = catch(|| parse_plugin_output(&[1, 2])).unwrap_err();
+/// assert!(err.message.contains("index out of bounds"));
+/// assert_eq!(err.location.line(), line!() - 5);
+///
+/// assert_eq!(catch(|| parse_plugin_output(&[1, 2, 3, 4])).unwrap(), 4);
+/// ```
+#[track_caller]
+pub fn catch<F, T>(f: F) -> Result<T, DebugError> 
+where
+    F: FnOnce() -> T,
+{
+    let caller = Location::caller();
+    install_hook();
+    CATCH_DEPTH.with(|depth| depth.set(depth.get() + 1));
+    let result = std::panic::catch_unwind(AssertUnwindSafe(f));
+    CATCH_DEPTH.with(|depth| depth.set(depth.get() - 1));
+    let payload = match result {
+        Ok(value) => return Ok(value),
+        Err(payload) => payload,
+    };
+    let message = match payload.downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => match payload.downcast_ref::<String>() {
+            Some(message) => message.clone(),
+            None => "Panic with a non-string payload".to_string(),
+        },
+    };
+    Err(match PANIC_LOCATION.with(|location| location.borrow_mut().take()) {
+        Some((file, line, column)) => DebugError::from_parts(message, file, line, column),
+        None => DebugError::new(message, caller),
+    })
+}
+
+/// Installs the panic hook that records the location of panics inside `catch`
+fn install_hook() 
+{
+    INSTALL_HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if CATCH_DEPTH.with(Cell::get) == 0 {
+                previous(info);
+                return;
+            }
+            let location = info.location().map(|location| (location.file().to_string(), location.line(), location.column()));
+            PANIC_LOCATION.with(|stored| *stored.borrow_mut() = location);
+        }));
+    });
+}
@@ -0,0 +1,256 @@
+//! Compiler style rendering that shows the source line of an error
+//!
+//! ```text
+//! error: Invalid port
+//!   --> src/config.rs:12:5
+//!    |
+//! 12 |     check!(port > 0);
+//!    |     ^
+//! ```
+//!
+//! The source line is read from the file at runtime. Binaries deployed without their
+//! sources can embed them with `embed_source!`, at the cost of a larger binary: each
+//! call embeds the complete file it is called in. Nothing is embedded unless you
+//! call the macro, so it is strictly opt-in. Call it once per file, e.g. at the start
+//! of `main` or of an initialization function of the module.
+
+use std::sync::RwLock;
+
+use crate::DebugError;
+
+static EMBEDDED_SOURCES: RwLock<Vec<(&'static str, &'static str)>> = RwLock::new(Vec::new());
+
+/// Registers the contents of a source file for `render_pretty`
+///
+/// Usually called through `embed_source!`. Registering the same file again replaces
+/// the previous contents.
+pub fn register_source(file: &'static str, contents: &'static str) 
+{
+    let mut sources = EMBEDDED_SOURCES.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+    sources.retain(|(registered, _)| *registered != file);
+    sources.push((file, contents));
+}
+
+/// Returns the contents of a source file, embedded sources first
+fn source_contents(file: &str) -> Option<String> 
+{
+    let sources = EMBEDDED_SOURCES.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some((_, contents)) = sources.iter().find(|(registered, _)| *registered == file) {
+        return Some(contents.to_string());
+    }
+    drop(sources);
+    std::fs::read_to_string(file).ok()
+}
+
+/// Returns the given line (starting at 1) of a file, embedded sources first
+pub(crate) fn source_line(file: &str, line: u32) -> Option<String> 
+{
+    let line_index = (line as usize).checked_sub(1)?;
+    source_contents(file)?.lines().nth(line_index).map(str::to_string)
+}
+
+impl DebugError 
+{
+    /// Renders the error like a compiler diagnostic, with the source line and a caret
+    ///
+    /// If the source file can't be found, only the message and location are rendered.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{register_source, DebugError};
+    ///
+    /// // The file doesn't exist on disk, like in a binary deployed without sources
+    /// register_source("src/deployed.rs", "fn main() {\n    let port = parse_port()?;\n}\n");
+    /// let err = DebugError::from_parts("Invalid port".to_string(), "src/deployed.rs".to_string(), 2, 16);
+    ///
+    /// assert_eq!(
+    ///     err.render_pretty(),
+    ///     "error: Invalid port\n  --> src/deployed.rs:2:16\n   |\n 2 |     let port = parse_port()?;\n   |                ^"
+    /// );
+    ///
+    /// let missing = DebugError::from_parts("Gone".to_string(), "src/missing.rs".to_string(), 1, 1);
+    /// assert_eq!(missing.render_pretty(), "error: Gone\n  --> src/missing.rs:1:1");
+    /// ```
+    pub fn render_pretty(&self) -> String 
+    {
+        let mut rendered = format!("error: {}\n  --> {}", self.message, self.location);
+        if let Some(source) = source_line(self.location.file(), self.location.line()) {
+            let line_number = self.location.line().to_string();
+            let gutter = " ".repeat(line_number.len() + 2);
+            let caret_offset = " ".repeat(self.location.column().saturating_sub(1) as usize);
+            rendered.push_str(&format!("\n{}|", gutter));
+            rendered.push_str(&format!("\n {} | {}", line_number, source));
+            rendered.push_str(&format!("\n{}| {}^", gutter, caret_offset));
+        }
+        rendered
+    }
+
+    /// Renders the error like `render_pretty`, with lines around the error line
+    ///
+    /// The error line is marked with `>` and a caret under the column. The window is
+    /// cut at the start and end of the file. If the source file can't be found, only
+    /// the message and location are rendered.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::DebugError;
+    ///
+    /// // tests/fixtures/config_loader.rs has 6 lines, the error is on line 4
+    /// let err = DebugError::from_parts("Port too low".to_string(), "tests/fixtures/config_loader.rs".to_string(), 4, 12);
+    ///
+    /// assert_eq!(
+    ///     err.render_pretty_context(1, 1),
+    ///     "error: Port too low\n  --> tests/fixtures/config_loader.rs:4:12\n   |\n \
+    ///      3 |     let port = parse_port(&text)?;\n\
+    ///      >4 |     check!(port > 1024);\n   |            ^\n \
+    ///      5 |     Ok(Config { port })"
+    /// );
+    ///
+    /// // The window ends with the file
+    /// let rendered = err.render_pretty_context(0, 10);
+    /// assert!(rendered.starts_with("error: Port too low\n  --> tests/fixtures/config_loader.rs:4:12\n   |\n>4 |"));
+    /// assert!(rendered.ends_with("\n 6 | }"));
+    ///
+    /// let missing = DebugError::from_parts("Gone".to_string(), "src/missing.rs".to_string(), 1, 1);
+    /// assert_eq!(missing.render_pretty_context(2, 2), "error: Gone\n  --> src/missing.rs:1:1");
+    /// ```
+    pub fn render_pretty_context(&self, lines_before: usize, lines_after: usize) -> String 
+    {
+        let mut rendered = format!("error: {}\n  --> {}", self.message, self.location);
+        let Some(contents) = source_contents(self.location.file()) else {
+            return rendered;
+        };
+        let lines: Vec<&str> = contents.lines().collect();
+        let error_line = self.location.line() as usize;
+        if error_line == 0 || error_line > lines.len() {
+            return rendered;
+        }
+        let first = error_line.saturating_sub(lines_before).max(1);
+        let last = error_line.saturating_add(lines_after).min(lines.len());
+        let width = last.to_string().len();
+        let gutter = " ".repeat(width + 2);
+        rendered.push_str(&format!("\n{}|", gutter));
+        for line_number in first..=last {
+            let marker = if line_number == error_line { '>' } else { ' ' };
+            rendered.push_str(&format!("\n{}{:>width$} | {}", marker, line_number, lines[line_number - 1], width = width));
+            if line_number == error_line {
+                let caret_offset = " ".repeat(self.location.column().saturating_sub(1) as usize);
+                rendered.push_str(&format!("\n{}| {}^", gutter, caret_offset));
+            }
+        }
+        rendered
+    }
+
+    /// Renders the error word-wrapped to `width` columns, for narrow terminals
+    ///
+    /// The message is wrapped first, then the location and each suggestion follow on
+    /// their own lines as `  at ...` and `  help: ...`. Words longer than a line are
+    /// broken hard; only the location is never broken, so a path can't be misread.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::DebugError;
+    ///
+    /// let err = DebugError::from_parts(
+    ///     "Could not reach the license server https://licenses.example.com/v2/activation within 30 seconds".to_string(),
+    ///     "src/licensing/activation.rs".to_string(),
+    ///     118,
+    ///     9,
+    /// )
+    /// .suggest("Check the proxy settings in ~/.config/app/network.toml");
+    ///
+    /// let rendered = err.render_wrapped(40);
+    /// assert_eq!(
+    ///     rendered,
+    ///     "Could not reach the license server\n\
+    ///      https://licenses.example.com/v2/activati\n\
+    ///      on within 30 seconds\n\
+    ///      \x20 at src/licensing/activation.rs:118:9\n\
+    ///      \x20 help: Check the proxy settings in\n\
+    ///      \x20       ~/.config/app/network.toml"
+    /// );
+    /// assert!(rendered.lines().all(|line| line.chars().count() <= 40));
+    /// ```
+    pub fn render_wrapped(&self, width: usize) -> String 
+    {
+        let mut lines = Vec::new();
+        wrap_words(&self.display_message(), width, "", &mut lines);
+        lines.push(format!("  at {}", self.location));
+        for suggestion in self.suggestions() {
+            let before = lines.len();
+            wrap_words(suggestion, width, "        ", &mut lines);
+            if let Some(first) = lines.get_mut(before) {
+                first.replace_range(..8, "  help: ");
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// Word-wraps text into lines of at most `width` columns, each starting with `indent`
+fn wrap_words(text: &str, width: usize, indent: &str, lines: &mut Vec<String>) 
+{
+    let available = width.saturating_sub(indent.len()).max(1);
+    let mut line = String::new();
+    let mut line_len = 0;
+    for word in text.split_whitespace() {
+        let mut word = word;
+        let mut word_len = word.chars().count();
+        if line_len > 0 && line_len + 1 + word_len > available {
+            lines.push(format!("{}{}", indent, line));
+            line.clear();
+            line_len = 0;
+        }
+        // Hard-break words that don't fit on a line of their own
+        while word_len > available {
+            if line_len > 0 {
+                lines.push(format!("{}{}", indent, line));
+                line.clear();
+                line_len = 0;
+            }
+            let split = word.char_indices().nth(available).map_or(word.len(), |(index, _)| index);
+            lines.push(format!("{}{}", indent, &word[..split]));
+            word = &word[split..];
+            word_len -= available;
+        }
+        if word_len == 0 {
+            continue;
+        }
+        if line_len > 0 {
+            line.push(' ');
+            line_len += 1;
+        }
+        line.push_str(word);
+        line_len += word_len;
+    }
+    if line_len > 0 || lines.is_empty() {
+        lines.push(format!("{}{}", indent, line));
+    }
+}
+
+/// Embeds the source of the current file so `render_pretty` works without sources
+///
+/// The file is included at compile time and registered under the path `file!()`
+/// returns, which is also the path stored in the locations of errors created in the
+/// file. The path is resolved relative to `CARGO_MANIFEST_DIR`, so the macro only
+/// works in the package root of a workspace; embed member crates with
+/// `register_source` and `include_str!` instead.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// fn main() {
+///     debug_error::embed_source!();
+///     // ...
+/// }
+/// ```
+#[macro_export]
+macro_rules! embed_source
+{
+    () => {{
+        $crate::register_source(file!(), include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", file!())));
+    }};
+}
@@ -0,0 +1,67 @@
+//! Summaries over many errors, e.g. for end-of-run reports in batch jobs
+
+use std::collections::HashMap;
+
+use crate::DebugError;
+
+/// Groups errors by their code, errors without a code are grouped under `None`
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error, group_by_code};
+///
+/// let errors = vec![
+///     debug_error!("Timeout on users").with_code("DB-001"),
+///     debug_error!("Bad request").with_code("HTTP-400"),
+///     debug_error!("Timeout on orders").with_code("DB-001"),
+///     debug_error!("Unexpected"),
+/// ];
+///
+/// let groups = group_by_code(&errors);
+/// assert_eq!(groups.len(), 3);
+/// assert_eq!(groups[&Some("DB-001")].len(), 2);
+/// assert_eq!(groups[&Some("HTTP-400")].len(), 1);
+/// assert_eq!(groups[&None].len(), 1);
+/// ```
+pub fn group_by_code(errors: &[DebugError]) -> HashMap<Option<&'static str>, Vec<&DebugError>> 
+{
+    let mut groups: HashMap<Option<&'static str>, Vec<&DebugError>> = HashMap::new();
+    for err in errors {
+        groups.entry(err.code()).or_default().push(err);
+    }
+    groups
+}
+
+/// Renders the number of errors per code, one code per line
+///
+/// Codes are sorted alphabetically, errors without a code are counted last.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error, summarize};
+///
+/// let errors = vec![
+///     debug_error!("Timeout on users").with_code("DB-001"),
+///     debug_error!("Unexpected"),
+///     debug_error!("Bad request").with_code("HTTP-400"),
+///     debug_error!("Timeout on orders").with_code("DB-001"),
+/// ];
+///
+/// assert_eq!(summarize(&errors), "DB-001: 2\nHTTP-400: 1\n(no code): 1");
+/// ```
+pub fn summarize(errors: &[DebugError]) -> String 
+{
+    let mut counts: Vec<(Option<&'static str>, usize)> = group_by_code(errors)
+        .into_iter()
+        .map(|(code, group)| (code, group.len()))
+        .collect();
+    // `None` sorts first, so compare with it mapped to the end
+    counts.sort_by_key(|(code, _)| (code.is_none(), *code));
+    counts
+        .iter()
+        .map(|(code, count)| format!("{}: {}", code.unwrap_or("(no code)"), count))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,46 @@
+//! Process resource usage at error time, enabled with the `resource-snapshot` feature
+//!
+//! Helps diagnosing resource leaks: when errors start piling up, the snapshot shows
+//! whether the process was running out of file descriptors or memory. Only Linux is
+//! supported, on other platforms no snapshot is taken.
+
+/// Open file descriptors and resident memory of the process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceSnapshot 
+{
+    /// Number of open file descriptors
+    pub open_fds: usize,
+    /// Resident set size in bytes
+    pub rss_bytes: u64,
+}
+
+impl std::fmt::Display for ResourceSnapshot 
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result 
+    {
+        write!(f, "{} open fds, {} bytes rss", self.open_fds, self.rss_bytes)
+    }
+}
+
+/// Takes a snapshot from `/proc/self`, `None` if it can't be read
+#[cfg(target_os = "linux")]
+pub(crate) fn capture() -> Option<ResourceSnapshot> 
+{
+    let open_fds = std::fs::read_dir("/proc/self/fd").ok()?.count();
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let rss_kb: u64 = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(ResourceSnapshot { open_fds, rss_bytes: rss_kb * 1024 })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn capture() -> Option<ResourceSnapshot> 
+{
+    None
+}
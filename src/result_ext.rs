@@ -0,0 +1,446 @@
+//! Extension methods for `Result<T, DebugError>` and results of other errors
+//!
+//! Methods that take a message or a value have a `_with` variant taking a closure
+//! instead, like `context_with` for `context`. The closure only runs on the error
+//! path, so costly context doesn't slow down the success path.
+
+use crate::{DebugError, Kind, LocationInfo};
+
+/// Convenience methods for results carrying a `DebugError`
+pub trait ResultExt<T> 
+{
+    /// Logs the error (if any) at its stored level and discards the result
+    ///
+    /// Meant for fire-and-forget paths like cleanup, where a failure should be visible
+    /// but not propagated. Replaces `let _ = x.map_err(|e| { error!("{}", e); });`.
+    /// Errors that were logged already (see `DebugError::is_logged`) aren't logged again.
+    /// The record is built like the one of `debug_error_with_log!`: its target is the
+    /// module the error was created in, its file and line are the error's location, and
+    /// suppressed codes and the log limit apply.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{debug_error, set_suppressed_codes, DebugError, ResultExt};
+    /// # debug_error::__capture_logs();
+    ///
+    /// fn remove_temp_file() -> Result<(), DebugError> {
+    ///     Err(debug_error!("Temp file already removed"))
+    /// }
+    ///
+    /// let err = remove_temp_file().unwrap_err();
+    /// let location = err.location.clone();
+    /// Err::<(), _>(err).ignore_logged();
+    ///
+    /// set_suppressed_codes(&["FS-404"]);
+    /// Err::<(), _>(debug_error!("Temp dir gone").with_code("FS-404")).ignore_logged();
+    ///
+    /// let records = debug_error::__captured_logs();
+    /// assert_eq!(records.len(), 1);
+    /// assert_eq!(records[0].level, log::Level::Error);
+    /// assert_eq!(records[0].target, module_path!());
+    /// assert_eq!(records[0].file.as_deref(), Some(location.file()));
+    /// assert_eq!(records[0].line, Some(location.line()));
+    /// assert!(records[0].message.starts_with("Error: Temp file already removed at "));
+    /// ```
+    fn ignore_logged(self);
+
+    /// Converts into an `Option`, logging the error (if any) at its stored level
+    ///
+    /// `Result::ok()` plus logging, replaces `.map_err(|e| { error!("{}", e); }).ok()`.
+    /// Errors that were logged already aren't logged again.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{debug_error, DebugError, ResultExt};
+    /// # debug_error::__capture_logs();
+    ///
+    /// fn read_theme(name: &str) -> Result<String, DebugError> {
+    ///     match name {
+    ///         "dark" => Ok("#000".to_string()),
+    ///         _ => Err(debug_error!("Unknown theme {}", name)),
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(read_theme("dark").ok_logged(), Some("#000".to_string()));
+    /// assert!(debug_error::__captured_logs().is_empty());
+    ///
+    /// assert_eq!(read_theme("neon").ok_logged(), None);
+    /// let records = debug_error::__captured_logs();
+    /// assert_eq!(records.len(), 1);
+    /// assert!(records[0].message.starts_with("Error: Unknown theme neon at "));
+    /// ```
+    fn ok_logged(self) -> Option<T>;
+
+    /// Returns the value, or `default` after handing the error to the subscribers
+    ///
+    /// The error isn't logged, it's passed to `ErrorSubscriber::on_recorded` of every
+    /// subscriber instead. Meant for resilient pipelines that track skipped failures in
+    /// metrics without writing a log line for each of them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{add_subscriber, debug_error, DebugError, ErrorSubscriber, ResultExt};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// #[derive(Default)]
+    /// struct SkippedRows(Mutex<Vec<String>>);
+    ///
+    /// impl ErrorSubscriber for SkippedRows {
+    ///     fn on_error(&self, _: &DebugError) {}
+    ///
+    ///     fn on_recorded(&self, err: &DebugError) {
+    ///         self.0.lock().unwrap().push(err.message.to_string());
+    ///     }
+    /// }
+    ///
+    /// let skipped = Arc::new(SkippedRows::default());
+    /// add_subscriber(skipped.clone());
+    ///
+    /// let price: Result<u32, DebugError> = Err(debug_error!("Row 7: price missing"));
+    /// assert_eq!(price.unwrap_or_record(0), 0);
+    /// assert_eq!(Ok::<u32, DebugError>(12).unwrap_or_record(0), 12);
+    ///
+    /// assert_eq!(*skipped.0.lock().unwrap(), ["Row 7: price missing"]);
+    /// ```
+    fn unwrap_or_record(self, default: T) -> T;
+
+    /// Records the caller location as a propagation hop of the error (if any)
+    ///
+    /// Use it right before `?`, see `DebugError::propagated` for why `?` alone can't
+    /// record hops. Hops are only recorded with the `track-propagation` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "track-propagation")]
+    /// # {
+    /// use debug_error::{debug_error, DebugError, ResultExt};
+    ///
+    /// fn read_config() -> Result<(), DebugError> {
+    ///     Err(debug_error!("Config missing"))
+    /// }
+    ///
+    /// fn load_settings() -> Result<(), DebugError> {
+    ///     read_config().propagate()?;
+    ///     Ok(())
+    /// }
+    ///
+    /// fn init() -> Result<(), DebugError> {
+    ///     load_settings().propagate()?;
+    ///     Ok(())
+    /// }
+    ///
+    /// fn main_loop() -> Result<(), DebugError> {
+    ///     init().propagate()?;
+    ///     Ok(())
+    /// }
+    ///
+    /// let err = main_loop().unwrap_err();
+    /// let hops: Vec<u32> = err.propagation().iter().map(|hop| hop.line()).collect();
+    /// assert_eq!(hops.len(), 3);
+    /// // Innermost hop first, each one in a later function
+    /// assert!(hops[0] < hops[1] && hops[1] < hops[2]);
+    /// # }
+    /// ```
+    fn propagate(self) -> Self;
+
+    /// Runs a closure on the error (if any) and returns the result unchanged
+    ///
+    /// Like `Result::inspect_err`, but the closure also gets the error's location, which
+    /// is handy for counters or metrics keyed by call site.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{debug_error, DebugError, ResultExt};
+    ///
+    /// let result: Result<u32, DebugError> = Err(debug_error!("Rate limited"));
+    /// let expected_line = line!() - 1;
+    ///
+    /// let mut seen = Vec::new();
+    /// let result = result.inspect_err_debug(|err, location| {
+    ///     seen.push((err.message.to_string(), location.line()));
+    /// });
+    ///
+    /// assert_eq!(seen, vec![("Rate limited".to_string(), expected_line)]);
+    /// assert_eq!(result.unwrap_err().message, "Rate limited");
+    ///
+    /// let ok: Result<u32, DebugError> = Ok(5);
+    /// assert_eq!(ok.inspect_err_debug(|_, _| panic!("not called")).unwrap(), 5);
+    /// ```
+    fn inspect_err_debug<F>(self, f: F) -> Self 
+    where
+        F: FnOnce(&DebugError, &LocationInfo);
+
+    /// Adds a key-value pair to the error (if any), see `DebugError::with_kv`
+    ///
+    /// The value is only formatted on the error path, so the happy path stays free of
+    /// allocations. Handy for threading request-specific context onto errors as they
+    /// propagate up.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{debug_error, DebugError, ResultExt};
+    /// use std::fmt;
+    ///
+    /// struct OrderId(u64);
+    ///
+    /// impl fmt::Display for OrderId {
+    ///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    ///         assert!(self.0 != 1, "formatted on the happy path");
+    ///         write!(f, "ord-{}", self.0)
+    ///     }
+    /// }
+    ///
+    /// fn ship(id: u64) -> Result<(), DebugError> {
+    ///     let result = if id == 1 { Ok(()) } else { Err(debug_error!("Carrier rejected parcel")) };
+    ///     result.kv_err("order_id", OrderId(id))
+    /// }
+    ///
+    /// assert!(ship(1).is_ok());
+    ///
+    /// let err = ship(2).unwrap_err();
+    /// assert_eq!(err.kv(), [("order_id", "ord-2".to_string())]);
+    /// ```
+    fn kv_err(self, key: &'static str, value: impl std::fmt::Display) -> Result<T, DebugError>;
+
+    /// Like `kv_err`, but the value is only computed on the error path
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{debug_error, DebugError, ResultExt};
+    ///
+    /// let ok: Result<u32, DebugError> = Ok(3);
+    /// assert_eq!(ok.kv_err_with("queue_depth", || -> u32 { panic!("not called") }).unwrap(), 3);
+    ///
+    /// let result: Result<u32, DebugError> = Err(debug_error!("Queue full"));
+    /// let err = result.kv_err_with("queue_depth", || 1024).unwrap_err();
+    /// assert_eq!(err.kv(), [("queue_depth", "1024".to_string())]);
+    /// ```
+    fn kv_err_with<F, V>(self, key: &'static str, f: F) -> Result<T, DebugError> 
+    where
+        F: FnOnce() -> V,
+        V: std::fmt::Display;
+}
+
+impl<T> ResultExt<T> for Result<T, DebugError> 
+{
+    fn ignore_logged(self) 
+    {
+        if let Err(err) = self {
+            err.log_unless_logged();
+        }
+    }
+
+    #[track_caller]
+    fn propagate(self) -> Self 
+    {
+        // Not `map_err`, the hop has to be recorded with the location of our caller
+        match self {
+            Ok(value) => Ok(value),
+            Err(err) => Err(err.propagated()),
+        }
+    }
+
+    fn inspect_err_debug<F>(self, f: F) -> Self 
+    where
+        F: FnOnce(&DebugError, &LocationInfo),
+    {
+        if let Err(err) = &self {
+            f(err, &err.location);
+        }
+        self
+    }
+
+    fn kv_err(self, key: &'static str, value: impl std::fmt::Display) -> Result<T, DebugError> 
+    {
+        self.map_err(|err| err.with_kv(key, value))
+    }
+
+    fn kv_err_with<F, V>(self, key: &'static str, f: F) -> Result<T, DebugError> 
+    where
+        F: FnOnce() -> V,
+        V: std::fmt::Display,
+    {
+        self.map_err(|err| err.with_kv(key, f()))
+    }
+
+    fn ok_logged(self) -> Option<T> 
+    {
+        match self {
+            Ok(value) => Some(value),
+            Err(err) => {
+                err.log_unless_logged();
+                None
+            }
+        }
+    }
+
+    fn unwrap_or_record(self, default: T) -> T 
+    {
+        match self {
+            Ok(value) => value,
+            Err(err) => {
+                crate::subscriber::notify_recorded(&err);
+                default
+            }
+        }
+    }
+}
+
+/// Conversions into `DebugError` for results carrying any other error
+pub trait ErrorResultExt<T> 
+{
+    /// Converts the error (if any) into a `DebugError` of the given kind
+    ///
+    /// The original error is kept as the source and its message is used as the message.
+    /// The location is where `kind_err` is called, which makes it a one-stop combinator
+    /// for boundary code wrapping low-level errors.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{DebugError, ErrorResultExt, Kind};
+    /// use std::io;
+    ///
+    /// fn open_profile() -> Result<String, DebugError> {
+    ///     let result: Result<String, io::Error> = Err(io::Error::new(io::ErrorKind::NotFound, "profile.json missing"));
+    ///     result.kind_err(Kind::NotFound)
+    /// }
+    ///
+    /// let err = open_profile().unwrap_err();
+    /// assert_eq!(err.kind(), Some(Kind::NotFound));
+    /// assert_eq!(err.message, "profile.json missing");
+    ///
+    /// let source = std::error::Error::source(&err).unwrap();
+    /// assert_eq!(source.downcast_ref::<io::Error>().unwrap().kind(), io::ErrorKind::NotFound);
+    /// ```
+    fn kind_err(self, kind: Kind) -> Result<T, DebugError>;
+
+    /// Wraps the error (if any) in one `DebugError` level per message
+    ///
+    /// Works like one `map_err` per message in order: the first message wraps the
+    /// original error and the last one ends up outermost. Every level gets the location
+    /// of the `contexts` call. Handy for generated code that collects context strings.
+    /// Without messages the error is only converted, keeping it as the source.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{DebugError, ErrorResultExt};
+    /// use std::io;
+    ///
+    /// let result: Result<(), io::Error> = Err(io::Error::other("disk full"));
+    /// let err = result.contexts(&["writing row 12", "exporting report", "nightly job"]).unwrap_err();
+    ///
+    /// let chain: Vec<String> = err.chain().map(|level| level.to_string()).collect();
+    /// assert_eq!(chain.len(), 4);
+    /// assert!(chain[0].starts_with("nightly job at "));
+    /// assert!(chain[1].starts_with("exporting report at "));
+    /// assert!(chain[2].starts_with("writing row 12 at "));
+    /// assert_eq!(chain[3], "disk full");
+    /// ```
+    fn contexts(self, msgs: &[&str]) -> Result<T, DebugError>;
+
+    /// Wraps the error (if any) in a `DebugError` with the message
+    ///
+    /// The original error becomes the source and the location is where `context` is
+    /// called. Works for `DebugError` results too, adding one level to the chain.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{DebugError, ErrorResultExt};
+    ///
+    /// fn read_port(text: &str) -> Result<u16, DebugError> {
+    ///     text.parse::<u16>().context("Invalid port")
+    /// }
+    ///
+    /// assert_eq!(read_port("8080").unwrap(), 8080);
+    ///
+    /// let err = read_port("http").unwrap_err();
+    /// assert_eq!(err.message, "Invalid port");
+    /// assert_eq!(err.chain().nth(1).unwrap().to_string(), "invalid digit found in string");
+    /// ```
+    fn context(self, msg: &str) -> Result<T, DebugError>;
+
+    /// Like `context`, but the message is only built on the error path
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{DebugError, ErrorResultExt};
+    /// use std::cell::Cell;
+    ///
+    /// let calls = Cell::new(0);
+    /// let describe = |path: &str| {
+    ///     calls.set(calls.get() + 1);
+    ///     format!("Invalid number in {}", path)
+    /// };
+    ///
+    /// let ok = "42".parse::<u32>().context_with(|| describe("limits.toml"));
+    /// assert_eq!(ok.unwrap(), 42);
+    /// assert_eq!(calls.get(), 0);
+    ///
+    /// let err = "forty".parse::<u32>().context_with(|| describe("limits.toml")).unwrap_err();
+    /// assert_eq!(err.message, "Invalid number in limits.toml");
+    /// assert_eq!(calls.get(), 1);
+    /// ```
+    fn context_with<F>(self, f: F) -> Result<T, DebugError> 
+    where
+        F: FnOnce() -> String;
+}
+
+impl<T, E> ErrorResultExt<T> for Result<T, E> 
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    #[track_caller]
+    fn kind_err(self, kind: Kind) -> Result<T, DebugError> 
+    {
+        match self {
+            Ok(value) => Ok(value),
+            Err(err) => Err(DebugError::new(err.to_string(), std::panic::Location::caller()).with_kind(kind).with_source(err)),
+        }
+    }
+
+    #[track_caller]
+    fn contexts(self, msgs: &[&str]) -> Result<T, DebugError> 
+    {
+        let err = match self {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        };
+        let location = std::panic::Location::caller();
+        let Some((first, rest)) = msgs.split_first() else {
+            return Err(DebugError::new(err.to_string(), location).with_source(err));
+        };
+        let innermost = DebugError::new(first.to_string(), location).with_source(err);
+        Err(rest.iter().fold(innermost, |inner, msg| DebugError::new(msg.to_string(), location).with_source(inner)))
+    }
+
+    #[track_caller]
+    fn context(self, msg: &str) -> Result<T, DebugError> 
+    {
+        match self {
+            Ok(value) => Ok(value),
+            Err(err) => Err(DebugError::new(msg.to_string(), std::panic::Location::caller()).with_source(err)),
+        }
+    }
+
+    #[track_caller]
+    fn context_with<F>(self, f: F) -> Result<T, DebugError> 
+    where
+        F: FnOnce() -> String,
+    {
+        match self {
+            Ok(value) => Ok(value),
+            Err(err) => Err(DebugError::new(f(), std::panic::Location::caller()).with_source(err)),
+        }
+    }
+}
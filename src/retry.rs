@@ -0,0 +1,200 @@
+//! Retrying of operations that fail with retryable errors
+//!
+//! `retry` blocks the thread between attempts, `retry_backoff` of the `async` feature
+//! is its async counterpart.
+
+use std::hash::{BuildHasher, RandomState};
+use std::time::Duration;
+#[cfg(feature = "async")]
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+use crate::DebugError;
+
+/// Returns the delay before the given retry (starting at 1), with jitter
+///
+/// The delay doubles with every retry. Half of it is fixed and the other half random,
+/// so clients failing at the same time don't retry in lockstep.
+fn backoff_delay(base: Duration, retry: u32) -> Duration 
+{
+    let delay = base.saturating_mul(2u32.saturating_pow(retry - 1));
+    let half = delay / 2;
+    let random = RandomState::new().hash_one(retry);
+    let jitter_nanos = match half.as_nanos() {
+        0 => 0,
+        nanos => (u128::from(random) % nanos) as u64,
+    };
+    half + Duration::from_nanos(jitter_nanos)
+}
+
+/// Decides what to do with the error of attempt `attempt`, `None` means retry
+///
+/// Returned errors get the attempt recorded, exhausted ones also say so in the message.
+fn finish(mut err: DebugError, attempt: u32, max: u32) -> Option<DebugError> 
+{
+    if !err.is_retryable() {
+        return Some(err.with_attempt(attempt, max));
+    }
+    if attempt == max {
+        // In place, `with_attempt` already renders the count as a parenthetical
+        err.message = format!("Gave up after {} attempts: {}", attempt, err.message).into();
+        return Some(err.with_attempt(attempt, max));
+    }
+    None
+}
+
+/// Runs an operation, retrying retryable errors with exponential backoff
+///
+/// Makes up to `max` attempts. Before retry `n` it sleeps about `base * 2^(n-1)`, with
+/// jitter. Errors that aren't retryable (see `DebugError::is_retryable`) are returned
+/// right away. When all attempts fail, the last error is returned with its message
+/// prefixed by the number of attempts, keeping its location and source. Returned
+/// errors note the attempt they happened on, see `DebugError::attempt`.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error, retry, DebugError, Kind};
+/// use std::error::Error;
+/// use std::time::Duration;
+///
+/// let mut calls = 0;
+/// let result: Result<(), DebugError> = retry(3, Duration::from_millis(1), || {
+///     calls += 1;
+///     Err(debug_error!("Registry unreachable").with_kind(Kind::Unavailable))
+/// });
+///
+/// let err = result.unwrap_err();
+/// assert_eq!(calls, 3);
+/// assert_eq!(err.attempt(), Some((3, 3)));
+/// assert_eq!(err.message, "Gave up after 3 attempts: Registry unreachable");
+/// assert!(err.to_string().contains("Gave up after 3 attempts: Registry unreachable (attempt 3/3) at "));
+/// assert!(err.source().is_none());
+///
+/// // Not retryable, returned on the first attempt
+/// let result: Result<(), DebugError> = retry(3, Duration::from_millis(1), || {
+///     Err(debug_error!("Bad manifest").with_kind(Kind::InvalidInput))
+/// });
+/// assert_eq!(result.unwrap_err().attempt(), Some((1, 3)));
+/// ```
+pub fn retry<F, T>(max: u32, base: Duration, mut f: F) -> Result<T, DebugError> 
+where
+    F: FnMut() -> Result<T, DebugError>,
+{
+    let max = max.max(1);
+    let mut attempt = 1;
+    loop {
+        let err = match f() {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        };
+        if let Some(err) = finish(err, attempt, max) {
+            return Err(err);
+        }
+        std::thread::sleep(backoff_delay(base, attempt));
+        attempt += 1;
+    }
+}
+
+/// Future that completes after a delay, without depending on an async runtime
+///
+/// A helper thread sleeps and wakes the task, which is fine for the few waits of a
+/// retry loop.
+#[cfg(feature = "async")]
+struct Delay 
+{
+    state: Arc<Mutex<(bool, Option<Waker>)>>,
+}
+
+#[cfg(feature = "async")]
+impl Delay 
+{
+    fn new(duration: Duration) -> Self 
+    {
+        let state = Arc::new(Mutex::new((false, None::<Waker>)));
+        let thread_state = Arc::clone(&state);
+        std::thread::spawn(move || {
+            std::thread::sleep(duration);
+            let mut state = thread_state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            state.0 = true;
+            if let Some(waker) = state.1.take() {
+                waker.wake();
+            }
+        });
+        Delay { state }
+    }
+}
+
+#[cfg(feature = "async")]
+impl Future for Delay 
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> 
+    {
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if state.0 {
+            return Poll::Ready(());
+        }
+        state.1 = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+/// Runs an async operation, retrying retryable errors with exponential backoff
+///
+/// Works like `retry`, but waits asynchronously between attempts. The waits don't
+/// need any particular async runtime. Needs the `async` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error, retry_backoff, DebugError, Kind};
+/// use std::sync::atomic::{AtomicU32, Ordering};
+/// use std::time::Duration;
+///
+/// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+///
+/// // Fails twice, then succeeds
+/// let attempts = AtomicU32::new(0);
+/// let result = runtime.block_on(retry_backoff(5, Duration::from_millis(1), || async {
+///     match attempts.fetch_add(1, Ordering::SeqCst) {
+///         0 | 1 => Err(debug_error!("Broker unreachable").with_kind(Kind::Unavailable)),
+///         _ => Ok("delivered"),
+///     }
+/// }));
+/// assert_eq!(result.unwrap(), "delivered");
+/// assert_eq!(attempts.load(Ordering::SeqCst), 3);
+///
+/// // Gives up after `max` attempts
+/// let result: Result<(), DebugError> = runtime.block_on(retry_backoff(3, Duration::from_millis(1), || async {
+///     Err(debug_error!("Broker unreachable").with_kind(Kind::Unavailable))
+/// }));
+/// let err = result.unwrap_err();
+/// assert_eq!(err.message, "Gave up after 3 attempts: Broker unreachable");
+/// assert_eq!(err.attempt(), Some((3, 3)));
+/// ```
+#[cfg(feature = "async")]
+pub async fn retry_backoff<F, Fut, T>(max: u32, base: Duration, mut f: F) -> Result<T, DebugError> 
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, DebugError>>,
+{
+    let max = max.max(1);
+    let mut attempt = 1;
+    loop {
+        let err = match f().await {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        };
+        if let Some(err) = finish(err, attempt, max) {
+            return Err(err);
+        }
+        Delay::new(backoff_delay(base, attempt)).await;
+        attempt += 1;
+    }
+}
@@ -0,0 +1,144 @@
+//! Sentry integration, enabled with the `sentry` feature
+//!
+//! `DebugError::capture` turns an error into a Sentry event, so errors flow to Sentry
+//! without mapping them by hand, and `set_sentry_autocapture` captures every new
+//! error automatically. The Sentry client itself (DSN, transport, release)
+//! is initialized by the application as usual with `sentry::init`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use ::sentry::protocol::{Event, Exception, Level};
+
+use crate::DebugError;
+
+static AUTOCAPTURE: AtomicBool = AtomicBool::new(false);
+
+/// Captures every newly created `DebugError` to Sentry when enabled
+///
+/// Disabled by default. With autocapture on, you don't need to call `capture`
+/// yourself, but every error is reported, including ones that are handled later.
+/// Errors are captured in the constructor, so the event has the message, the
+/// location and the `error_context` entries, but nothing added by builder calls
+/// afterwards, like `with_code`, `with_source` or key-value fields. Call `capture`
+/// yourself on the finished error when the event needs those.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error, error_context, set_sentry_autocapture};
+/// # use std::sync::{Arc, Mutex};
+/// # use sentry::{ClientOptions, Envelope, Hub, Transport};
+/// # #[derive(Default)]
+/// # struct Collect(Mutex<Vec<sentry::protocol::Event<'static>>>);
+/// # impl Transport for Collect {
+/// #     fn send_envelope(&self, envelope: Envelope) {
+/// #         if let Some(event) = envelope.event() {
+/// #             self.0.lock().unwrap().push(event.clone());
+/// #         }
+/// #     }
+/// # }
+/// # let transport = Arc::new(Collect::default());
+/// # let mut options = ClientOptions::default();
+/// # options.dsn = Some("https://public@sentry.invalid/1".parse().unwrap());
+/// # options.transport = Some(Arc::new(transport.clone()));
+/// # let hub = Arc::new(Hub::new(Some(Arc::new(options.into())), Arc::new(Default::default())));
+/// # Hub::run(hub, || {
+///
+/// set_sentry_autocapture(true);
+///
+/// let _g = error_context("syncing orders");
+/// let io_err = std::io::Error::other("read timed out");
+/// let _ = debug_error!("Order sync failed").with_code("SYNC-001").with_source(io_err);
+/// # });
+///
+/// let events = transport.0.lock().unwrap();
+/// assert_eq!(events.len(), 1);
+/// assert_eq!(events[0].message.as_deref(), Some("Order sync failed"));
+/// assert_eq!(events[0].tags.get("context.0").map(String::as_str), Some("syncing orders"));
+///
+/// // Added after creation, so not part of the event
+/// assert_eq!(events[0].tags.get("code"), None);
+/// assert_eq!(events[0].exception.len(), 1);
+/// ```
+pub fn set_sentry_autocapture(enabled: bool) 
+{
+    AUTOCAPTURE.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn sentry_autocapture() -> bool 
+{
+    AUTOCAPTURE.load(Ordering::Relaxed)
+}
+
+impl DebugError 
+{
+    /// Builds the Sentry event for this error
+    ///
+    /// The message becomes the event message, the location becomes the culprit and
+    /// the source chain becomes the exception list (innermost cause first, as Sentry
+    /// expects). The code, the context entries from `error_context` and the key-value
+    /// pairs of `with_kv` are added as tags. A pair named like one of the other tags
+    /// doesn't replace it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{debug_error, error_context};
+    ///
+    /// let _g = error_context("syncing orders");
+    /// let io_err = std::io::Error::new(std::io::ErrorKind::TimedOut, "read timed out");
+    /// let err = debug_error!("Order sync failed").with_code("SYNC-001").with_kv("shop_id", 17).with_source(io_err);
+    ///
+    /// let event = err.to_sentry_event();
+    /// assert_eq!(event.message.as_deref(), Some("Order sync failed"));
+    /// assert_eq!(event.culprit, Some(format!("{}:{}", err.location.file(), err.location.line())));
+    /// assert_eq!(event.tags.get("code").map(String::as_str), Some("SYNC-001"));
+    /// assert_eq!(event.tags.get("context.0").map(String::as_str), Some("syncing orders"));
+    /// assert_eq!(event.tags.get("shop_id").map(String::as_str), Some("17"));
+    /// assert_eq!(event.exception.len(), 2);
+    /// assert_eq!(event.exception[0].value.as_deref(), Some("read timed out"));
+    /// assert_eq!(event.exception[1].ty, "DebugError");
+    /// ```
+    pub fn to_sentry_event(&self) -> Event<'static> 
+    {
+        let mut exceptions: Vec<Exception> = self
+            .chain()
+            .map(|err| Exception {
+                ty: if err.is::<DebugError>() { "DebugError".to_string() } else { "Error".to_string() },
+                value: Some(match err.downcast_ref::<DebugError>() {
+                    Some(debug_err) => debug_err.message.to_string(),
+                    None => err.to_string(),
+                }),
+                ..Default::default()
+            })
+            .collect();
+        exceptions.reverse();
+
+        let mut event = Event {
+            level: Level::Error,
+            message: Some(self.message.to_string()),
+            culprit: Some(format!("{}:{}", self.location.file(), self.location.line())),
+            exception: exceptions.into(),
+            ..Default::default()
+        };
+        for (key, value) in self.kv() {
+            event.tags.insert(key.to_string(), value.clone());
+        }
+        if let Some(code) = self.code() {
+            event.tags.insert("code".to_string(), code.to_string());
+        }
+        for (i, context) in self.context().iter().enumerate() {
+            event.tags.insert(format!("context.{}", i), context.clone());
+        }
+        event
+    }
+
+    /// Sends this error to Sentry through the current hub
+    ///
+    /// Does nothing if the application hasn't initialized a Sentry client.
+    pub fn capture(&self) 
+    {
+        ::sentry::capture_event(self.to_sentry_event());
+    }
+}
+
@@ -0,0 +1,58 @@
+//! Minimal logger writing to stderr, enabled with the `simple-logger` feature
+//!
+//! Lets examples and tests show the output of `debug_error_with_log!` without pulling
+//! in a logging crate. Real applications are better served by `env_logger` or similar.
+
+use ::log::{Log, Metadata, Record};
+
+static LOGGER: SimpleLogger = SimpleLogger;
+
+/// Logger installed by `init_simple_logger`
+struct SimpleLogger;
+
+impl Log for SimpleLogger 
+{
+    fn enabled(&self, metadata: &Metadata) -> bool 
+    {
+        metadata.level() <= ::log::max_level()
+    }
+
+    fn log(&self, record: &Record) 
+    {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        match (record.file(), record.line()) {
+            (Some(file), Some(line)) => eprintln!("{} {}:{} {}", record.level(), file, line, record.args()),
+            _ => eprintln!("{} {}", record.level(), record.args()),
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs a logger that writes "LEVEL file:line message" lines to stderr
+///
+/// Records above `level` are dropped. `RUST_LOG` isn't read, the given level is all
+/// there is. Does nothing if a logger is installed already.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error_with_log, init_simple_logger};
+///
+/// init_simple_logger(log::Level::Warn);
+///
+/// assert!(log::log_enabled!(log::Level::Error));
+/// assert!(log::log_enabled!(log::Level::Warn));
+/// assert!(!log::log_enabled!(log::Level::Info));
+///
+/// // Prints "ERROR src/main.rs:12 Error: Disk quota exceeded at src/main.rs:12:5"
+/// let _ = debug_error_with_log!("Disk quota exceeded");
+/// ```
+pub fn init_simple_logger(level: ::log::Level) 
+{
+    if ::log::set_logger(&LOGGER).is_ok() {
+        ::log::set_max_level(level.to_level_filter());
+    }
+}
@@ -0,0 +1,72 @@
+//! Errors built at compile time, for static tables of possible errors
+
+use std::panic::Location;
+
+use crate::DebugError;
+
+/// Message and location of an error, constructible in const contexts
+///
+/// `DebugError` owns a `String`, so it can't be built at compile time. A
+/// `StaticDebugError` can, which allows tables like `const ERRORS: &[StaticDebugError]`.
+/// Convert an entry into a `DebugError` with `From` when the error happens, the
+/// location stays the one of the table entry.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{DebugError, StaticDebugError};
+///
+/// const ERRORS: &[StaticDebugError] = &[
+///     StaticDebugError::new_static("Unsupported image format"),
+///     StaticDebugError::new_static("Image too large"),
+/// ];
+/// let first_entry_line = line!() - 3;
+///
+/// fn check_size(pixels: u64) -> Result<(), DebugError> {
+///     if pixels > 50_000_000 {
+///         return Err(ERRORS[1].into());
+///     }
+///     Ok(())
+/// }
+///
+/// let err = check_size(80_000_000).unwrap_err();
+/// assert_eq!(err.message, "Image too large");
+/// assert_eq!(err.location.line(), first_entry_line + 1);
+/// assert_eq!(ERRORS[0].message(), "Unsupported image format");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaticDebugError 
+{
+    message: &'static str,
+    location: &'static Location<'static>,
+}
+
+impl StaticDebugError 
+{
+    /// Creates an entry with the caller location
+    #[track_caller]
+    pub const fn new_static(message: &'static str) -> Self 
+    {
+        Self { message, location: Location::caller() }
+    }
+
+    /// Returns the message
+    pub const fn message(&self) -> &'static str 
+    {
+        self.message
+    }
+
+    /// Returns the location the entry was created at
+    pub const fn location(&self) -> &'static Location<'static> 
+    {
+        self.location
+    }
+}
+
+impl From<StaticDebugError> for DebugError 
+{
+    fn from(err: StaticDebugError) -> Self 
+    {
+        DebugError::new(err.message, err.location)
+    }
+}
@@ -0,0 +1,56 @@
+//! Process wide statistics about created errors
+//!
+//! Cheap enough to be always on: creating an error costs two atomic increments, one
+//! for the counter and one for the sequence number.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static ERROR_COUNT: AtomicU64 = AtomicU64::new(0);
+static NEXT_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// Returns how many `DebugError`s were created since startup or the last reset
+///
+/// Meant for health checks and dashboards. The counter uses relaxed ordering: the
+/// count itself is exact, but it isn't synchronized with other memory operations, so
+/// a thread may see a count that doesn't yet include an error created on another
+/// thread a moment ago. That is fine for monitoring and avoids any fences on the
+/// error path.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error, error_count, reset_error_count};
+///
+/// reset_error_count();
+/// for i in 0..5 {
+///     let _ = debug_error!("Failure {}", i);
+/// }
+/// assert_eq!(error_count(), 5);
+///
+/// reset_error_count();
+/// assert_eq!(error_count(), 0);
+/// ```
+pub fn error_count() -> u64 
+{
+    ERROR_COUNT.load(Ordering::Relaxed)
+}
+
+/// Resets the counter of `error_count` to zero
+pub fn reset_error_count() 
+{
+    ERROR_COUNT.store(0, Ordering::Relaxed);
+}
+
+/// Counts a newly created error
+pub(crate) fn count_created() 
+{
+    ERROR_COUNT.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Returns the sequence number for a newly created error
+///
+/// Not reset by `reset_error_count`, so sequence numbers never repeat.
+pub(crate) fn next_seq() -> u64 
+{
+    NEXT_SEQ.fetch_add(1, Ordering::Relaxed)
+}
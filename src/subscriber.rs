@@ -0,0 +1,83 @@
+//! Observers that are notified of every new error, e.g. for metrics or UI integrations
+
+use std::sync::{Arc, RwLock};
+
+use crate::DebugError;
+
+static SUBSCRIBERS: RwLock<Vec<Arc<dyn ErrorSubscriber + Send + Sync>>> = RwLock::new(Vec::new());
+
+/// Observer of error creation, registered with `add_subscriber`
+///
+/// `on_error` runs synchronously on the thread creating the error, so keep it quick,
+/// e.g. bump a counter or hand the error to a channel. Creating errors inside
+/// `on_error` notifies the subscribers again, so avoid that.
+pub trait ErrorSubscriber 
+{
+    fn on_error(&self, err: &DebugError);
+
+    /// Called for errors that were replaced by a default, see `ResultExt::unwrap_or_record`
+    fn on_recorded(&self, _err: &DebugError) {}
+}
+
+/// Registers a subscriber that is notified of every `DebugError` created from now on
+///
+/// Subscribers are notified in the order they were added and can't be removed.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{add_subscriber, debug_error, DebugError, ErrorSubscriber};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::{Arc, Mutex};
+///
+/// #[derive(Default)]
+/// struct Metrics(AtomicUsize);
+///
+/// impl ErrorSubscriber for Metrics {
+///     fn on_error(&self, _: &DebugError) {
+///         self.0.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// #[derive(Default)]
+/// struct ErrorPanel(Mutex<Vec<String>>);
+///
+/// impl ErrorSubscriber for ErrorPanel {
+///     fn on_error(&self, err: &DebugError) {
+///         self.0.lock().unwrap().push(err.message.to_string());
+///     }
+/// }
+///
+/// let metrics = Arc::new(Metrics::default());
+/// let panel = Arc::new(ErrorPanel::default());
+/// add_subscriber(metrics.clone());
+/// add_subscriber(panel.clone());
+///
+/// let _ = debug_error!("Printer offline");
+///
+/// assert_eq!(metrics.0.load(Ordering::Relaxed), 1);
+/// assert_eq!(*panel.0.lock().unwrap(), ["Printer offline"]);
+/// ```
+pub fn add_subscriber(subscriber: Arc<dyn ErrorSubscriber + Send + Sync>) 
+{
+    SUBSCRIBERS.write().unwrap_or_else(|poisoned| poisoned.into_inner()).push(subscriber);
+}
+
+/// Notifies all subscribers of a new error
+pub(crate) fn notify(err: &DebugError) 
+{
+    // Clone the list, so subscribers can create errors without deadlocking
+    let subscribers = SUBSCRIBERS.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone();
+    for subscriber in &subscribers {
+        subscriber.on_error(err);
+    }
+}
+
+/// Notifies all subscribers of an error that was recorded instead of handled
+pub(crate) fn notify_recorded(err: &DebugError) 
+{
+    let subscribers = SUBSCRIBERS.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone();
+    for subscriber in &subscribers {
+        subscriber.on_recorded(err);
+    }
+}
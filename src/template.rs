@@ -0,0 +1,77 @@
+//! Named message templates, so teams word similar errors the same way everywhere
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+static TEMPLATES: RwLock<Option<HashMap<&'static str, String>>> = RwLock::new(None);
+
+/// Registers a message template for `debug_error_tmpl!` under the given name
+///
+/// Placeholders are names in braces, like `{db}`. Registering a name again replaces
+/// its template. Placeholders without a value are left in the message as they are,
+/// so a forgotten value shows up instead of disappearing silently.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error_tmpl, register_template};
+///
+/// register_template("db_timeout", "Database {db} timed out after {secs}s");
+///
+/// let err = debug_error_tmpl!("db_timeout", db = "users", secs = 30);
+/// assert_eq!(err.message, "Database users timed out after 30s");
+/// assert_eq!(err.location.line(), line!() - 2);
+///
+/// let err = debug_error_tmpl!("db_timeout", db = "orders");
+/// assert_eq!(err.message, "Database orders timed out after {secs}s");
+///
+/// // Unknown templates fall back to their name
+/// assert_eq!(debug_error_tmpl!("cache_miss").message, "cache_miss");
+/// ```
+pub fn register_template(name: &'static str, template: impl Into<String>) 
+{
+    TEMPLATES
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .get_or_insert_with(HashMap::new)
+        .insert(name, template.into());
+}
+
+/// Fills the placeholders of a registered template, used by `debug_error_tmpl!`
+#[doc(hidden)]
+pub fn __fill_template(name: &str, values: &[(&str, String)]) -> String 
+{
+    let templates = TEMPLATES.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let Some(template) = templates.as_ref().and_then(|templates| templates.get(name)) else {
+        return name.to_string();
+    };
+    let mut message = String::with_capacity(template.len());
+    let mut rest = template.as_str();
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}') else {
+            break;
+        };
+        message.push_str(&rest[..open]);
+        let placeholder = &rest[open..open + close + 1];
+        match values.iter().find(|(key, _)| *key == &placeholder[1..placeholder.len() - 1]) {
+            Some((_, value)) => message.push_str(value),
+            None => message.push_str(placeholder),
+        }
+        rest = &rest[open + close + 1..];
+    }
+    message.push_str(rest);
+    message
+}
+
+/// Creates a DebugError from a template registered with `register_template`
+///
+/// Takes the template name followed by `name = value` pairs for the placeholders.
+/// Values can be anything that implements `Display`.
+#[macro_export]
+macro_rules! debug_error_tmpl
+{
+    ($name:expr $(, $key:ident = $value:expr)* $(,)?) => {{
+        let message = $crate::__fill_template($name, &[$((stringify!($key), ::std::string::ToString::to_string(&$value))),*]);
+        $crate::DebugError::new(message, std::panic::Location::caller()).__in_module(module_path!())
+    }};
+}
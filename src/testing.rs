@@ -0,0 +1,294 @@
+//! Assertions for tests of error handling code
+
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex, Once};
+
+use crate::{DebugError, ErrorSubscriber, Kind};
+
+thread_local! {
+    /// Errors created on this thread during `assert_no_errors`, `None` outside of it
+    static CREATED: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+static INSTALL_WATCHER: Once = Once::new();
+static CAPTURED_LOGS: Mutex<Vec<CapturedRecord>> = Mutex::new(Vec::new());
+
+/// Asserts that the chain of `err` matches the expected messages, outermost first
+///
+/// Each level of the chain has to contain the corresponding substring, and the chain
+/// has to be exactly as long as `expected`. On a mismatch the panic message shows the
+/// complete actual chain, so you don't need to debug-print it yourself.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{assert_chain, debug_error};
+///
+/// let io_err = std::io::Error::new(std::io::ErrorKind::NotFound, "settings.toml missing");
+/// let config_err = debug_error!("Failed to read config").with_source(io_err);
+/// let err = debug_error!("Startup failed").with_source(config_err);
+///
+/// assert_chain(&err, &["Startup failed", "read config", "settings.toml"]);
+///
+/// let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+///     assert_chain(&err, &["Startup failed", "database"]);
+/// }));
+/// assert!(result.is_err());
+/// ```
+#[track_caller]
+pub fn assert_chain(err: &DebugError, expected: &[&str]) 
+{
+    let actual: Vec<String> = err.chain().map(|err| err.to_string()).collect();
+    let matches = actual.len() == expected.len()
+        && actual.iter().zip(expected).all(|(message, expected)| message.contains(expected));
+    if !matches {
+        let rendered: Vec<String> = actual.iter().enumerate().map(|(i, message)| format!("  {}: {}", i, message)).collect();
+        panic!(
+            "error chain doesn't match\nexpected: {:?}\nactual chain:\n{}",
+            expected,
+            rendered.join("\n")
+        );
+    }
+}
+
+/// Returns true if the error matches all given criteria, `None` matches anything
+///
+/// Checks the code, a substring of the message and the kind, so tests only pin down
+/// what they care about. See `assert_error!` for the assertion form.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error, error_matches, Kind};
+///
+/// let err = debug_error!("Upstream timeout after 30s").with_code("HTTP-504").with_kind(Kind::Timeout);
+///
+/// assert!(error_matches(&err, Some("HTTP-504"), None, None));
+/// assert!(error_matches(&err, None, Some("timeout"), Some(Kind::Timeout)));
+/// assert!(error_matches(&err, None, None, None));
+/// assert!(!error_matches(&err, Some("HTTP-502"), Some("timeout"), None));
+/// assert!(!error_matches(&err, None, None, Some(Kind::Unavailable)));
+/// ```
+pub fn error_matches(err: &DebugError, code: Option<&str>, msg_contains: Option<&str>, kind: Option<Kind>) -> bool 
+{
+    code.is_none_or(|code| err.code() == Some(code))
+        && msg_contains.is_none_or(|text| err.message.contains(text))
+        && kind.is_none_or(|kind| err.kind() == Some(kind))
+}
+
+/// Asserts that a result is an error matching the given criteria
+///
+/// Takes any of `code = ...`, `msg = ...` (a substring of the message) and
+/// `kind = ...`, in any order, see `error_matches`. Panics if the result is `Ok` or
+/// the error doesn't match, showing the complete error.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{assert_error, debug_error, DebugError, Kind};
+///
+/// fn fetch() -> Result<String, DebugError> {
+///     Err(debug_error!("Upstream timeout after 30s").with_code("HTTP-504").with_kind(Kind::Timeout))
+/// }
+///
+/// assert_error!(fetch(), code = "HTTP-504", msg = "timeout");
+/// assert_error!(fetch(), kind = Kind::Timeout);
+/// assert_error!(fetch());
+///
+/// let mismatch = std::panic::catch_unwind(|| assert_error!(fetch(), msg = "refused"));
+/// assert!(mismatch.is_err());
+/// let ok = std::panic::catch_unwind(|| assert_error!(Ok::<u32, DebugError>(1), code = "HTTP-504"));
+/// assert!(ok.is_err());
+/// ```
+#[macro_export]
+macro_rules! assert_error
+{
+    ($result:expr $(, $key:ident = $value:expr)* $(,)?) => {{
+        let result = $result;
+        let err = match &result {
+            Ok(_) => panic!("expected an error, got Ok"),
+            Err(err) => err,
+        };
+        let mut criteria: (Option<&str>, Option<&str>, Option<$crate::Kind>) = (None, None, None);
+        $( $crate::__assert_error_criterion!(criteria, $key, $value); )*
+        if !$crate::error_matches(err, criteria.0, criteria.1, criteria.2) {
+            panic!(
+                "error doesn't match\ncode: {:?}, msg: {:?}, kind: {:?}\nactual error: {:#}\ncode: {:?}, kind: {:?}",
+                criteria.0, criteria.1, criteria.2, err, err.code(), err.kind()
+            );
+        }
+    }};
+}
+
+/// Sets one criterion of `assert_error!`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __assert_error_criterion
+{
+    ($criteria:ident, code, $value:expr) => {
+        $criteria.0 = Some($value);
+    };
+    ($criteria:ident, msg, $value:expr) => {
+        $criteria.1 = Some($value);
+    };
+    ($criteria:ident, kind, $value:expr) => {
+        $criteria.2 = Some($value);
+    };
+}
+
+/// Runs `f` and panics if it created any `DebugError`
+///
+/// Only errors created on the calling thread count, so tests running in parallel don't
+/// interfere. The panic message lists the errors that were created.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{assert_no_errors, debug_error};
+///
+/// fn parse_port(text: &str) -> Result<u16, debug_error::DebugError> {
+///     text.parse().map_err(|_| debug_error!("Invalid port {:?}", text))
+/// }
+///
+/// assert_no_errors(|| {
+///     parse_port("8080").unwrap();
+/// });
+///
+/// let result = std::panic::catch_unwind(|| {
+///     assert_no_errors(|| {
+///         let _ = parse_port("http");
+///     })
+/// });
+/// let message = result.unwrap_err().downcast::<String>().unwrap();
+/// assert!(message.contains("Invalid port \"http\""));
+/// ```
+#[track_caller]
+pub fn assert_no_errors<F: FnOnce()>(f: F) 
+{
+    INSTALL_WATCHER.call_once(|| crate::subscriber::add_subscriber(Arc::new(CreationWatcher)));
+    let previous = CREATED.with(|created| created.borrow_mut().replace(Vec::new()));
+    let restore = RestoreCreated(previous);
+    f();
+    let created = CREATED.with(|created| created.borrow_mut().take()).unwrap_or_default();
+    drop(restore);
+    if !created.is_empty() {
+        let rendered: Vec<String> = created.iter().map(|err| format!("  - {}", err)).collect();
+        panic!("expected no errors, {} were created:\n{}", created.len(), rendered.join("\n"));
+    }
+}
+
+/// Records the errors created during `assert_no_errors`
+struct CreationWatcher;
+
+impl ErrorSubscriber for CreationWatcher 
+{
+    fn on_error(&self, err: &DebugError) 
+    {
+        CREATED.with(|created| {
+            if let Some(created) = created.borrow_mut().as_mut() {
+                created.push(err.to_string());
+            }
+        });
+    }
+}
+
+/// Puts back the recording of an outer `assert_no_errors`, also when `f` panics
+struct RestoreCreated(Option<Vec<String>>);
+
+impl Drop for RestoreCreated 
+{
+    fn drop(&mut self) 
+    {
+        let previous = self.0.take();
+        CREATED.with(|created| *created.borrow_mut() = previous);
+    }
+}
+
+/// A log record collected by `__capture_logs`
+#[doc(hidden)]
+#[derive(Debug, Clone)]
+pub struct CapturedRecord 
+{
+    pub level: ::log::Level,
+    pub target: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub message: String,
+    pub fields: Vec<(String, String)>,
+}
+
+impl CapturedRecord 
+{
+    /// Returns the value of a structured field of the record
+    pub fn field(&self, key: &str) -> Option<&str> 
+    {
+        self.fields.iter().find(|(name, _)| name == key).map(|(_, value)| value.as_str())
+    }
+}
+
+/// Logger of `__capture_logs`, collects every record
+struct CaptureLogger;
+
+impl ::log::Log for CaptureLogger 
+{
+    fn enabled(&self, _: &::log::Metadata) -> bool 
+    {
+        true
+    }
+
+    fn log(&self, record: &::log::Record) 
+    {
+        struct Fields(Vec<(String, String)>);
+        impl<'kvs> ::log::kv::VisitSource<'kvs> for Fields 
+        {
+            fn visit_pair(&mut self, key: ::log::kv::Key<'kvs>, value: ::log::kv::Value<'kvs>) -> Result<(), ::log::kv::Error> 
+            {
+                self.0.push((key.to_string(), value.to_string()));
+                Ok(())
+            }
+        }
+        let mut fields = Fields(Vec::new());
+        let _ = record.key_values().visit(&mut fields);
+        let captured = CapturedRecord {
+            level: record.level(),
+            target: record.target().to_string(),
+            file: record.file().map(str::to_string),
+            line: record.line(),
+            message: record.args().to_string(),
+            fields: fields.0,
+        };
+        CAPTURED_LOGS.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(captured);
+    }
+
+    fn flush(&self) 
+    {
+    }
+}
+
+/// Installs a logger that collects every record, for the doctests of this crate
+///
+/// Enables all levels. Does nothing if another logger is installed already.
+#[doc(hidden)]
+pub fn __capture_logs() 
+{
+    let _ = ::log::set_logger(&CaptureLogger);
+    ::log::set_max_level(::log::LevelFilter::Trace);
+}
+
+/// Returns the records collected since `__capture_logs`, oldest first
+#[doc(hidden)]
+pub fn __captured_logs() -> Vec<CapturedRecord> 
+{
+    CAPTURED_LOGS.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+}
+
+/// Sets an environment variable, for the doctests of this crate
+///
+/// Each doctest runs in its own process and sets its variables before it starts
+/// any threads, so nothing reads the environment concurrently.
+#[doc(hidden)]
+pub fn __set_env_var(key: &str, value: &str) 
+{
+    // SAFETY: see above, doctests set variables while they are single-threaded
+    unsafe { std::env::set_var(key, value) };
+}
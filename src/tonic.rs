@@ -0,0 +1,58 @@
+//! Conversion into gRPC statuses for services built on `tonic`
+
+use crate::{DebugError, Kind};
+
+/// Converts an error into a gRPC status, the status code is picked from the kind
+///
+/// | Kind | Code |
+/// |------|------|
+/// | `NotFound` | `NotFound` |
+/// | `PermissionDenied` | `PermissionDenied` |
+/// | `InvalidInput` | `InvalidArgument` |
+/// | others or none | `Internal` |
+///
+/// The status message is `DebugError::public_message`, so internal details never reach
+/// the client. The complete error is logged at its level before converting, unless it
+/// was logged already.
+///
+/// # Examples
+///
+/// ```rust
+/// use debug_error::{debug_error, Kind};
+/// use tonic::{Code, Status};
+///
+/// let code_of = |kind: Option<Kind>| {
+///     let err = debug_error!("Lookup of /srv/data/users.db failed");
+///     let err = match kind {
+///         Some(kind) => err.with_kind(kind),
+///         None => err,
+///     };
+///     Status::from(err).code()
+/// };
+///
+/// assert_eq!(code_of(Some(Kind::NotFound)), Code::NotFound);
+/// assert_eq!(code_of(Some(Kind::PermissionDenied)), Code::PermissionDenied);
+/// assert_eq!(code_of(Some(Kind::InvalidInput)), Code::InvalidArgument);
+/// assert_eq!(code_of(Some(Kind::Timeout)), Code::Internal);
+/// assert_eq!(code_of(Some(Kind::Unavailable)), Code::Internal);
+/// assert_eq!(code_of(Some(Kind::Conflict)), Code::Internal);
+/// assert_eq!(code_of(Some(Kind::Internal)), Code::Internal);
+/// assert_eq!(code_of(None), Code::Internal);
+///
+/// let status = Status::from(debug_error!("Lookup of /srv/data/users.db failed"));
+/// assert_eq!(status.message(), "An internal error occurred");
+/// ```
+impl From<DebugError> for ::tonic::Status 
+{
+    fn from(err: DebugError) -> Self 
+    {
+        err.log_unless_logged();
+        let code = match err.kind() {
+            Some(Kind::NotFound) => ::tonic::Code::NotFound,
+            Some(Kind::PermissionDenied) => ::tonic::Code::PermissionDenied,
+            Some(Kind::InvalidInput) => ::tonic::Code::InvalidArgument,
+            _ => ::tonic::Code::Internal,
+        };
+        ::tonic::Status::new(code, err.public_message())
+    }
+}
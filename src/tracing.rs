@@ -0,0 +1,134 @@
+//! Fields of the current `tracing` span as key-value pairs of errors
+//!
+//! A `tracing::Span` can't be asked for its fields, they are only passed to the
+//! subscriber. `SpanFieldsLayer` keeps them in the span registry, where
+//! `DebugError::with_current_span` picks them up:
+//!
+//! ```rust
+//! use tracing_subscriber::layer::SubscriberExt;
+//!
+//! let subscriber = tracing_subscriber::registry().with(debug_error::SpanFieldsLayer);
+//! tracing::subscriber::set_global_default(subscriber).unwrap();
+//! ```
+
+use ::tracing::field::{Field, Visit};
+use ::tracing::span::{Attributes, Id, Record};
+use ::tracing::Subscriber;
+use ::tracing_subscriber::layer::{Context, Layer};
+use ::tracing_subscriber::registry::{LookupSpan, Registry};
+
+use crate::DebugError;
+
+/// Layer that records span fields for `DebugError::with_current_span`
+///
+/// Needs a subscriber built on `tracing_subscriber::Registry`, which is the case
+/// for `tracing_subscriber::registry()` and the `fmt` subscriber.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SpanFieldsLayer;
+
+/// Fields recorded for a span, stored in its registry extensions
+struct SpanFields(Vec<(&'static str, String)>);
+
+impl Visit for SpanFields 
+{
+    fn record_str(&mut self, field: &Field, value: &str) 
+    {
+        self.set(field.name(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) 
+    {
+        self.set(field.name(), format!("{:?}", value));
+    }
+}
+
+impl SpanFields 
+{
+    /// Sets a field, replacing the value of an earlier `Span::record`
+    fn set(&mut self, name: &'static str, value: String) 
+    {
+        match self.0.iter_mut().find(|(key, _)| *key == name) {
+            Some((_, existing)) => *existing = value,
+            None => self.0.push((name, value)),
+        }
+    }
+}
+
+impl<S> Layer<S> for SpanFieldsLayer 
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) 
+    {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = SpanFields(Vec::new());
+        attrs.record(&mut fields);
+        span.extensions_mut().insert(fields);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) 
+    {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        if let Some(fields) = span.extensions_mut().get_mut::<SpanFields>() {
+            values.record(fields);
+        }
+    }
+}
+
+impl DebugError 
+{
+    /// Copies the fields of the current span and its parents into the key-value pairs
+    ///
+    /// Fields of outer spans come first. Without `SpanFieldsLayer` in the subscriber, or
+    /// outside of any span, the error is returned unchanged. Like all key-value pairs,
+    /// the fields end up in `{:#}`, JSON, the journal fields, Sentry tags and the
+    /// OpenTelemetry attributes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use debug_error::{debug_error, SpanFieldsLayer};
+    /// use tracing_subscriber::layer::SubscriberExt;
+    ///
+    /// let subscriber = tracing_subscriber::registry().with(SpanFieldsLayer);
+    /// tracing::subscriber::with_default(subscriber, || {
+    ///     let request = tracing::info_span!("request", request_id = "4bf92f35", user_id = 42);
+    ///     let _request = request.enter();
+    ///     let query = tracing::info_span!("query", table = "orders");
+    ///     let _query = query.enter();
+    ///
+    ///     let err = debug_error!("Query timed out").with_current_span();
+    ///
+    ///     assert_eq!(
+    ///         err.kv(),
+    ///         [
+    ///             ("request_id", "4bf92f35".to_string()),
+    ///             ("user_id", "42".to_string()),
+    ///             ("table", "orders".to_string()),
+    ///         ]
+    ///     );
+    /// });
+    /// ```
+    pub fn with_current_span(self) -> Self 
+    {
+        let mut fields = Vec::new();
+        ::tracing::Span::current().with_subscriber(|(id, dispatch)| {
+            let Some(registry) = dispatch.downcast_ref::<Registry>() else {
+                return;
+            };
+            let Some(span) = registry.span(id) else {
+                return;
+            };
+            for span in span.scope().from_root() {
+                if let Some(recorded) = span.extensions().get::<SpanFields>() {
+                    fields.extend(recorded.0.iter().cloned());
+                }
+            }
+        });
+        fields.into_iter().fold(self, |err, (key, value)| err.with_kv(key, value))
+    }
+}
@@ -0,0 +1,6 @@
+fn load_config(path: &str) -> Result<Config, DebugError> {
+    let text = std::fs::read_to_string(path)?;
+    let port = parse_port(&text)?;
+    check!(port > 1024);
+    Ok(Config { port })
+}
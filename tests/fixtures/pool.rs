@@ -0,0 +1,7 @@
+// Included by the doctest of `DebugError::originated_in`, so errors come from another file
+use debug_error::{debug_error, DebugError};
+
+pub fn acquire_connection() -> DebugError 
+{
+    debug_error!("Pool exhausted")
+}